@@ -0,0 +1,42 @@
+//! Generates the table of the emojis of `@emoji` from `data/emojis.txt`, with the `emoji` feature.
+
+fn main() {
+    #[cfg(feature = "emoji")]
+    emojis();
+}
+
+#[cfg(feature = "emoji")]
+fn emojis() {
+    use std::env;
+    use std::fmt::Write;
+    use std::fs;
+    use std::path::Path;
+
+    const EMOJIS: &str = "data/emojis.txt";
+
+    println!("cargo:rerun-if-changed={EMOJIS}");
+
+    let data = fs::read_to_string(EMOJIS).expect("failed to read the emojis");
+    let mut map = phf_codegen::Map::new();
+    for (index, line) in data.lines().enumerate() {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let Some((shortcode, emoji)) = line.split_once('\t') else {
+            panic!("{EMOJIS}:{}: expected a shortcode and an emoji", index + 1);
+        };
+        map.entry(shortcode, &format!("{emoji:?}"));
+    }
+
+    let mut code = String::new();
+    writeln!(
+        code,
+        "pub(crate) static EMOJIS: phf::Map<&'static str, &'static str> = {};",
+        map.build()
+    )
+    .unwrap();
+
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join("emojis.rs");
+    fs::write(out, code).expect("failed to write the emojis");
+}
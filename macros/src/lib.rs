@@ -0,0 +1,264 @@
+//! Attribute macro to write the Rustdoc of an item with Doxygen.
+//!
+//! This crate is a dependency of its own rather than a feature of `doxygen-rs`, which can't
+//! re-export the macro as the macro depends on it for the translation.
+//!
+//! # Examples
+//!
+//! ```
+//! use doxygen_rs_macros::doxygen;
+//!
+//! /// @brief Adds two numbers.
+//! /// @param a The first number.
+//! /// @param b The second number.
+//! /// @returns The sum.
+//! #[doxygen]
+//! pub fn add(a: i32, b: i32) -> i32 {
+//!     a + b
+//! }
+//! ```
+
+use doxygen_rs::transform::to_token_stream;
+use proc_macro2::{Span, TokenStream};
+use quote::ToTokens;
+use syn::parse::Parser;
+use syn::visit_mut::{self, VisitMut};
+use syn::{
+    Attribute, Expr, ExprLit, Field, ForeignItem, ImplItem, Item, Lit, Meta, TraitItem, Variant,
+};
+
+/// Translates the Doxygen in the doc comments of the item to Rustdoc, including the doc comments
+/// of the items it contains (like the functions of a module or the fields of a struct).
+///
+/// # Errors
+///
+/// The translation errors of [`doxygen_rs::generator::rustdoc`] are reported as compile errors.
+#[proc_macro_attribute]
+pub fn doxygen(
+    args: proc_macro::TokenStream,
+    item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    if !args.is_empty() {
+        return syn::Error::new(Span::call_site(), "`#[doxygen]` doesn't take arguments")
+            .into_compile_error()
+            .into();
+    }
+
+    expand(item.into()).into()
+}
+
+fn expand(item: TokenStream) -> TokenStream {
+    let mut item = match syn::parse2::<Item>(item) {
+        Ok(item) => item,
+        Err(error) => return error.into_compile_error(),
+    };
+
+    let mut translator = Translator::default();
+    translator.visit_item_mut(&mut item);
+
+    let mut tokens = item.into_token_stream();
+    for error in translator.errors {
+        tokens.extend(error.into_compile_error());
+    }
+
+    tokens
+}
+
+/// Translates the doc comments of the visited items.
+#[derive(Default)]
+struct Translator {
+    errors: Vec<syn::Error>,
+}
+
+impl Translator {
+    fn translate(&mut self, attrs: &mut Vec<Attribute>) {
+        if let Err(error) = translate_attrs(attrs) {
+            self.errors.push(error);
+        }
+    }
+}
+
+impl VisitMut for Translator {
+    fn visit_item_mut(&mut self, item: &mut Item) {
+        if let Some(attrs) = item_attrs(item) {
+            self.translate(attrs);
+        }
+
+        visit_mut::visit_item_mut(self, item);
+    }
+
+    fn visit_impl_item_mut(&mut self, item: &mut ImplItem) {
+        let attrs = match item {
+            ImplItem::Const(item) => &mut item.attrs,
+            ImplItem::Fn(item) => &mut item.attrs,
+            ImplItem::Type(item) => &mut item.attrs,
+            ImplItem::Macro(item) => &mut item.attrs,
+            _ => return,
+        };
+        self.translate(attrs);
+
+        visit_mut::visit_impl_item_mut(self, item);
+    }
+
+    fn visit_trait_item_mut(&mut self, item: &mut TraitItem) {
+        let attrs = match item {
+            TraitItem::Const(item) => &mut item.attrs,
+            TraitItem::Fn(item) => &mut item.attrs,
+            TraitItem::Type(item) => &mut item.attrs,
+            TraitItem::Macro(item) => &mut item.attrs,
+            _ => return,
+        };
+        self.translate(attrs);
+
+        visit_mut::visit_trait_item_mut(self, item);
+    }
+
+    fn visit_foreign_item_mut(&mut self, item: &mut ForeignItem) {
+        let attrs = match item {
+            ForeignItem::Fn(item) => &mut item.attrs,
+            ForeignItem::Static(item) => &mut item.attrs,
+            ForeignItem::Type(item) => &mut item.attrs,
+            ForeignItem::Macro(item) => &mut item.attrs,
+            _ => return,
+        };
+        self.translate(attrs);
+
+        visit_mut::visit_foreign_item_mut(self, item);
+    }
+
+    fn visit_field_mut(&mut self, field: &mut Field) {
+        self.translate(&mut field.attrs);
+        visit_mut::visit_field_mut(self, field);
+    }
+
+    fn visit_variant_mut(&mut self, variant: &mut Variant) {
+        self.translate(&mut variant.attrs);
+        visit_mut::visit_variant_mut(self, variant);
+    }
+}
+
+fn item_attrs(item: &mut Item) -> Option<&mut Vec<Attribute>> {
+    Some(match item {
+        Item::Const(item) => &mut item.attrs,
+        Item::Enum(item) => &mut item.attrs,
+        Item::ExternCrate(item) => &mut item.attrs,
+        Item::Fn(item) => &mut item.attrs,
+        Item::ForeignMod(item) => &mut item.attrs,
+        Item::Impl(item) => &mut item.attrs,
+        Item::Macro(item) => &mut item.attrs,
+        Item::Mod(item) => &mut item.attrs,
+        Item::Static(item) => &mut item.attrs,
+        Item::Struct(item) => &mut item.attrs,
+        Item::Trait(item) => &mut item.attrs,
+        Item::TraitAlias(item) => &mut item.attrs,
+        Item::Type(item) => &mut item.attrs,
+        Item::Union(item) => &mut item.attrs,
+        Item::Use(item) => &mut item.attrs,
+        _ => return None,
+    })
+}
+
+/// Replaces the `#[doc = "..."]` attributes (the doc comments) with the translated ones. The doc
+/// comments are kept as they are if any of them isn't a string literal.
+fn translate_attrs(attrs: &mut Vec<Attribute>) -> syn::Result<()> {
+    let Some(position) = attrs.iter().position(is_doc) else {
+        return Ok(());
+    };
+
+    let mut lines = vec![];
+    for attr in attrs.iter().filter(|attr| is_doc(attr)) {
+        match &attr.meta {
+            Meta::NameValue(meta) => match &meta.value {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(lit), ..
+                }) => lines.push(lit.value()),
+                _ => return Ok(()),
+            },
+            _ => return Ok(()),
+        }
+    }
+
+    // Doc comments like `/// Example` start with the space after the slashes
+    let doxygen = lines
+        .iter()
+        .flat_map(|line| line.lines())
+        .map(|line| line.strip_prefix(' ').unwrap_or(line))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let span = attrs[position].bracket_token.span.join();
+    let rustdoc = doxygen_rs::generator::rustdoc(doxygen)
+        .map_err(|error| syn::Error::new(span, format!("invalid Doxygen: {error}")))?;
+
+    let translated = Attribute::parse_outer.parse2(to_token_stream(&rustdoc))?;
+
+    attrs.retain(|attr| !is_doc(attr));
+    attrs.splice(position..position, translated);
+
+    Ok(())
+}
+
+fn is_doc(attr: &Attribute) -> bool {
+    attr.path().is_ident("doc")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use quote::quote;
+
+    #[test]
+    fn translates_doc_comments() {
+        let result = expand(quote! {
+            /// @brief Example function.
+            /// @param a The value.
+            #[inline]
+            pub fn example(a: i32) {}
+        });
+
+        let expected = quote! {
+            #[doc = " Example function."]
+            #[doc = ""]
+            #[doc = " # Arguments"]
+            #[doc = ""]
+            #[doc = " * `a` - The value."]
+            #[inline]
+            pub fn example(a: i32) {}
+        };
+        assert_eq!(result.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn translates_nested_items() {
+        let result = expand(quote! {
+            mod example {
+                /// @brief Example struct.
+                struct Example {
+                    /// @a Styled field
+                    field: i32,
+                }
+            }
+        });
+
+        let expected = quote! {
+            mod example {
+                #[doc = " Example struct."]
+                struct Example {
+                    #[doc = " _Styled_ field"]
+                    field: i32,
+                }
+            }
+        };
+        assert_eq!(result.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn reports_errors() {
+        let result = expand(quote! {
+            /// Missing word @c
+            fn example() {}
+        });
+
+        assert!(result.to_string().contains("compile_error"));
+    }
+}
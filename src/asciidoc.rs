@@ -0,0 +1,338 @@
+//! Translation of Doxygen to AsciiDoc, for the documentation built with Asciidoctor or Antora.
+//!
+//! The Doxygen is parsed and split into sections like for the Rustdoc (see [`RustdocComment`]),
+//! and the sections are rendered with the AsciiDoc markup: the parameters become a description
+//! list, the notes and warnings become admonitions, and the code blocks become source blocks.
+
+use crate::comment::{convert_with_options, Direction, Param, ReturnValue, RustdocComment, Throws};
+use crate::generator::ParseError;
+use crate::markup::{self, Block, Inline, Sections};
+use crate::options::Options;
+use crate::tag::Tag;
+
+/// Translates a Doxygen comment to AsciiDoc.
+///
+/// # Examples
+///
+/// ```
+/// use doxygen_rs::asciidoc::asciidoc;
+///
+/// let input = "@brief Frees a @c buffer\n@param[in] buffer The buffer.\n@note Thread-safe.";
+///
+/// assert_eq!(
+///     asciidoc(input.into()).unwrap(),
+///     "Frees a `+buffer+`\n\nNOTE: Thread-safe.\n\n== Arguments\n\n`+buffer+` (in):: The buffer."
+/// );
+/// ```
+///
+/// # Errors
+///
+/// See [`crate::generator::rustdoc`].
+pub fn asciidoc(input: String) -> Result<String, ParseError> {
+    asciidoc_with_options(input, &Options::default())
+}
+
+/// Translates a Doxygen comment to AsciiDoc, using the given [`Options`].
+///
+/// # Errors
+///
+/// See [`crate::generator::rustdoc_with_options`].
+pub fn asciidoc_with_options(input: String, options: &Options) -> Result<String, ParseError> {
+    Ok(render(&convert_with_options(input, options)?, options))
+}
+
+/// Renders the sections of a translated comment as AsciiDoc, in the order of
+/// [`Options::section_order`] like the Rustdoc.
+///
+/// The headings of the sections are level 1 sections (`==`) with the default
+/// [`Options::heading_level`], as level 0 is the title of the document.
+pub fn render(comment: &RustdocComment, options: &Options) -> String {
+    markup::render(comment, options, &AsciiDoc)
+}
+
+/// Creates a section with a heading, like `== Arguments`, unless the content is empty.
+fn section_block(title: &str, content: String, options: &Options) -> String {
+    if content.is_empty() {
+        return content;
+    }
+
+    format!("{} {title}\n\n{content}", heading_prefix(options, 0))
+}
+
+/// Creates the prefix of a heading (like `==`) from [`Options::heading_level`], nested `depth`
+/// levels further.
+fn heading_prefix(options: &Options, depth: usize) -> String {
+    "=".repeat((options.heading_level + depth).clamp(1, 5) + 1)
+}
+
+fn list(items: &[String], options: &Options) -> String {
+    items
+        .iter()
+        .map(|item| format!("* {}", markdown(item, options)))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Creates a description list, like the parameters, with one entry per term.
+fn description_list<'a>(entries: impl Iterator<Item = (String, &'a String)>) -> String {
+    entries
+        .map(|(term, description)| match description.is_empty() {
+            true => format!("{term}::"),
+            false => format!("{term}:: {}", inline(&description.replace('\n', " "))),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Creates a literal monospace text, like `` `+example+` ``.
+fn code(text: &str) -> String {
+    format!("`+{text}+`")
+}
+
+/// Translates the Markdown of the Rustdoc to AsciiDoc.
+fn markdown(text: &str, options: &Options) -> String {
+    let mut result = vec![];
+
+    for block in markup::blocks(text) {
+        match block {
+            Block::Heading { level, text } => {
+                let prefix = heading_prefix(options, level.saturating_sub(options.heading_level));
+                result.push(format!("{prefix} {}", inline(text)));
+            }
+            Block::Code { language, lines } => {
+                match language {
+                    "" | "text" => {}
+                    language => result.push(format!("[source,{language}]")),
+                }
+                result.push("----".into());
+                result.extend(lines.into_iter().map(String::from));
+                result.push("----".into());
+            }
+            Block::Quote(quote) => {
+                result.push(format!("____\n{}\n____", markdown(&quote, options)))
+            }
+            Block::ListItem {
+                ordered,
+                depth,
+                text,
+            } => {
+                let marker = match ordered {
+                    true => ".",
+                    false => "*",
+                };
+                result.push(format!("{} {}", marker.repeat(depth + 1), inline(text)));
+            }
+            Block::Line(line) => result.push(inline(line)),
+            Block::Blank => result.push(String::new()),
+        }
+    }
+
+    result.join("\n").trim().to_string()
+}
+
+fn inline(text: &str) -> String {
+    markup::inline(text, &AsciiDoc)
+}
+
+/// The sections of a comment in AsciiDoc.
+impl Sections for AsciiDoc {
+    fn markdown(&self, text: &str, options: &Options) -> String {
+        markdown(text, options)
+    }
+
+    /// Creates an admonition, like `NOTE: Text.`.
+    fn admonition(&self, tag: &Tag, text: &str, options: &Options) -> String {
+        let label = match tag {
+            Tag::Note => "NOTE",
+            Tag::Warning => "WARNING",
+            Tag::Important => "IMPORTANT",
+            Tag::Deprecated => {
+                let message = format!("Deprecated. {text}");
+                return format!("CAUTION: {}", markdown(message.trim_end(), options));
+            }
+            _ => return format!("Available since: {}", inline(text)),
+        };
+
+        format!("{label}: {}", markdown(text, options))
+    }
+
+    fn titled_list(&self, title: &str, items: &[String], options: &Options) -> String {
+        match items.is_empty() {
+            true => String::new(),
+            false => format!(".{title}\n{}", list(items, options)),
+        }
+    }
+
+    fn arguments(&self, params: &[Param], options: &Options) -> String {
+        let entries = params.iter().map(|param| {
+            let direction = match param.direction {
+                Some(Direction::In) => " (in)",
+                Some(Direction::Out) => " (out)",
+                Some(Direction::InOut) => " (in, out)",
+                None => "",
+            };
+            (
+                format!("{}{direction}", code(&param.name)),
+                &param.description,
+            )
+        });
+
+        section_block("Arguments", description_list(entries), options)
+    }
+
+    fn returns(&self, returns: Option<&str>, retvals: &[ReturnValue], options: &Options) -> String {
+        let mut content = returns
+            .map(|returns| markdown(returns, options))
+            .unwrap_or_default();
+        let retvals = description_list(
+            retvals
+                .iter()
+                .map(|retval| (code(&retval.value), &retval.description)),
+        );
+        if !content.is_empty() && !retvals.is_empty() {
+            content += "\n\n";
+        }
+        content += &retvals;
+
+        section_block("Returns", content, options)
+    }
+
+    fn errors(&self, throws: &[Throws], options: &Options) -> String {
+        let title = match options.throws_as_errors {
+            true => "Errors",
+            false => "Throws",
+        };
+        let entries = throws
+            .iter()
+            .map(|throws| (code(&throws.name), &throws.description));
+
+        section_block(title, description_list(entries), options)
+    }
+
+    fn safety(&self, preconditions: &[String], options: &Options) -> String {
+        section_block("Safety", list(preconditions, options), options)
+    }
+
+    fn see_also(&self, targets: &[String], options: &Options) -> String {
+        let content = targets
+            .iter()
+            .map(|target| format!("* {}", code(target)))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        section_block("See also", content, options)
+    }
+}
+
+/// The inline markup of AsciiDoc, where the autolinks (like `<https://example.com>`) are the same
+/// as in Markdown.
+struct AsciiDoc;
+
+impl Inline for AsciiDoc {
+    fn code(&self, code: &str) -> String {
+        self::code(code)
+    }
+
+    // A single asterisk is bold in AsciiDoc
+    fn italics(&self, text: &str) -> String {
+        format!("_{text}_")
+    }
+
+    fn link(&self, label: &str, url: &str) -> String {
+        format!("{url}[{}]", inline(label))
+    }
+
+    fn reference(&self, label: &str, anchor: &str) -> String {
+        format!("<<{anchor},{}>>", inline(label))
+    }
+
+    fn autolink(&self, url: &str) -> String {
+        format!("<{url}>")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sections() {
+        let input = "@brief Reads a file.\n\nDetails with @b bold and @e italics.\n@sa read_all\n@param[out] buffer The buffer.\n@param size\n@returns The size.\n@retval -1 Failure.\n@throws io_error When it fails.\n@pre The file is open.\n@warning Slow.\n@deprecated Use @ref read_all.";
+
+        assert_eq!(
+            asciidoc(input.into()).unwrap(),
+            "Reads a file.
+
+Details with **bold** and _italics_.
+
+WARNING: Slow.
+
+.Preconditions
+* The file is open.
+
+CAUTION: Deprecated. Use <<read_all,read_all>>.
+
+== Arguments
+
+`+buffer+` (out):: The buffer.
+`+size+`::
+
+== Returns
+
+The size.
+
+`+-1+`:: Failure.
+
+== Throws
+
+`+io_error+`:: When it fails.
+
+== See also
+
+* `+read_all+`"
+        );
+    }
+
+    #[test]
+    fn blocks() {
+        let input = "@brief Example\n\n# Usage\n- First\n  - Nested\n- Second\n\n1. One\n2. Two\n@code{.c}\nint *x = NULL;\n@endcode\nSee [the docs](https://example.com) or https://example.com.";
+
+        assert_eq!(
+            asciidoc(input.into()).unwrap(),
+            "Example
+
+== Usage
+* First
+** Nested
+* Second
+
+. One
+. Two
+
+[source,c]
+----
+int *x = NULL;
+----
+See https://example.com[the docs] or <https://example.com>."
+        );
+        assert_eq!(
+            asciidoc("@par Title\nContent text.".into()).unwrap(),
+            "== Title\nContent text."
+        );
+    }
+
+    #[test]
+    fn safety() {
+        let options = Options {
+            pre_as_safety: true,
+            heading_level: 2,
+            ..Default::default()
+        };
+        let input = "@brief Frees memory.\n@note First.\n@pre Valid.";
+
+        assert_eq!(
+            asciidoc_with_options(input.into(), &options).unwrap(),
+            "Frees memory.\n\nNOTE: First.\n\n=== Safety\n\n* Valid."
+        );
+    }
+}
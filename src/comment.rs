@@ -0,0 +1,969 @@
+//! Structured access to the translated Doxygen.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::generator::{self, paragraph_end, Generator, ParseError};
+use crate::options::Options;
+use crate::parser::{parse_recovering, GrammarItem};
+use crate::stats::Stats;
+use crate::tag::Tag;
+
+/// A Doxygen comment translated to Rustdoc, split into its sections.
+///
+/// The texts of the sections are Markdown, like the whole Rustdoc, which is rendered with
+/// [`ToString::to_string`].
+///
+/// # Examples
+///
+/// ```
+/// use doxygen_rs::comment::{convert, Direction};
+///
+/// let comment = convert("@brief Frees a @c buffer\n@param[in] buffer The buffer.".into()).unwrap();
+///
+/// assert_eq!(comment.brief, "Frees a `buffer`");
+/// assert_eq!(comment.params[0].name, "buffer");
+/// assert_eq!(comment.params[0].direction, Some(Direction::In));
+/// assert_eq!(comment.params[0].description, "The buffer.");
+/// assert_eq!(
+///     comment.to_string(),
+///     "Frees a `buffer`\n\n# Arguments\n\n* `buffer` (direction in) - The buffer."
+/// );
+/// ```
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct RustdocComment {
+    /// The brief description, from `@brief`. Empty if there's none.
+    pub brief: String,
+    /// The detailed description, made of the text that doesn't belong to any other section.
+    pub description: String,
+    /// The parameters, from `@param`.
+    pub params: Vec<Param>,
+    /// The description of the returned value, from `@returns`.
+    pub returns: Option<String>,
+    /// The specific returned values, from `@retval`.
+    pub retvals: Vec<ReturnValue>,
+    /// The thrown exceptions, from `@throws`.
+    pub throws: Vec<Throws>,
+    /// The preconditions, from `@pre`.
+    pub preconditions: Vec<String>,
+    /// The postconditions, from `@post`.
+    pub postconditions: Vec<String>,
+    /// The references, from `@see`.
+    pub see_also: Vec<String>,
+    /// The notes, from `@note` and `@remark`.
+    pub notes: Vec<String>,
+    /// The warnings, from `@warning`.
+    pub warnings: Vec<String>,
+    /// The important remarks, from `@important`.
+    pub important: Vec<String>,
+    /// The pages linked as children of the page being documented, from `@subpage`. Along with the
+    /// names of the pages, these make the hierarchy of a multi-page manual.
+    pub subpages: Vec<SubPage>,
+    /// The deprecation message, from `@deprecated`.
+    pub deprecated: Option<String>,
+    /// The version the item is available since, from `@since`.
+    pub since: Option<String>,
+    /// The groups defined or extended by the comment, from `@defgroup`, `@addtogroup` and
+    /// `@weakgroup`, and the groups of the item, from `@ingroup`, so the members documented in
+    /// several files can be gathered by group.
+    pub groups: Vec<String>,
+    /// The labels of the documented item, from `@qualifier`, like `static` or `(optional)`.
+    /// Binding generators can act on them, like marking the optional parameters.
+    pub qualifiers: Vec<String>,
+    /// The visibility of the documented members, from `@private`, `@privatesection` and similar.
+    /// Binding generators can use it to hide the private documentation.
+    pub visibility: Option<Visibility>,
+    rendered: String,
+}
+
+/// A mismatch between a [`RustdocComment`] and the signature of the function it documents, found
+/// by [`RustdocComment::check_signature`].
+#[cfg(feature = "syn")]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum SignatureLint {
+    /// A documented parameter that isn't in the signature, like a renamed one.
+    UnknownParam(String),
+    /// A parameter of the signature that isn't documented.
+    UndocumentedParam(String),
+    /// The function returns a value, but the comment has neither `@returns` nor `@retval`.
+    MissingReturns,
+}
+
+#[cfg(feature = "syn")]
+impl RustdocComment {
+    /// Checks the comment against the signature of the function it documents, like the FFI
+    /// declarations of a binding, returning the mismatches found.
+    ///
+    /// The parameters renamed by bindgen to avoid Rust keywords (like `type_` for `type`) match
+    /// their documented names, and a variadic signature matches a `@param ...`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use doxygen_rs::comment::{convert, SignatureLint};
+    ///
+    /// let comment = convert("@brief Opens a file.\n@param name The name.".into()).unwrap();
+    /// let signature = syn::parse_str("fn open(path: *const u8, flags: i32) -> i32").unwrap();
+    ///
+    /// assert_eq!(
+    ///     comment.check_signature(&signature),
+    ///     vec![
+    ///         SignatureLint::UnknownParam("name".into()),
+    ///         SignatureLint::UndocumentedParam("path".into()),
+    ///         SignatureLint::UndocumentedParam("flags".into()),
+    ///         SignatureLint::MissingReturns,
+    ///     ]
+    /// );
+    /// ```
+    pub fn check_signature(&self, signature: &syn::Signature) -> Vec<SignatureLint> {
+        let mut names = signature
+            .inputs
+            .iter()
+            .filter_map(|input| match input {
+                syn::FnArg::Typed(typed) => match &*typed.pat {
+                    syn::Pat::Ident(pat) => Some(pat.ident.to_string()),
+                    _ => None,
+                },
+                syn::FnArg::Receiver(_) => None,
+            })
+            .map(|name| name.trim_start_matches("r#").to_string())
+            .collect::<Vec<_>>();
+        if signature.variadic.is_some() {
+            names.push("...".into());
+        }
+
+        let matches = |documented: &str, name: &str| {
+            documented == name || name.strip_suffix('_') == Some(documented)
+        };
+
+        let mut lints = self
+            .params
+            .iter()
+            .filter(|param| !names.iter().any(|name| matches(&param.name, name)))
+            .map(|param| SignatureLint::UnknownParam(param.name.clone()))
+            .collect::<Vec<_>>();
+
+        lints.extend(
+            names
+                .into_iter()
+                .filter(|name| !self.params.iter().any(|param| matches(&param.name, name)))
+                .map(SignatureLint::UndocumentedParam),
+        );
+
+        let returns_value = match &signature.output {
+            syn::ReturnType::Default => false,
+            syn::ReturnType::Type(_, ty) => match &**ty {
+                syn::Type::Tuple(tuple) => !tuple.elems.is_empty(),
+                syn::Type::Never(_) => false,
+                _ => true,
+            },
+        };
+        if returns_value && self.returns.is_none() && self.retvals.is_empty() {
+            lints.push(SignatureLint::MissingReturns);
+        }
+
+        lints
+    }
+}
+
+/// A parameter of a [`RustdocComment`].
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Param {
+    /// The name of the parameter.
+    pub name: String,
+    /// The direction of the parameter, like `in` for `@param[in]`.
+    pub direction: Option<Direction>,
+    /// The description of the parameter.
+    pub description: String,
+}
+
+/// The direction of a [`Param`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Direction {
+    /// `@param[in]`, the parameter is read.
+    In,
+    /// `@param[out]`, the parameter is written.
+    Out,
+    /// `@param[in,out]`, the parameter is both read and written.
+    InOut,
+}
+
+/// A specific returned value of a [`RustdocComment`].
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct ReturnValue {
+    /// The returned value, like `0`.
+    pub value: String,
+    /// The description of the returned value.
+    pub description: String,
+}
+
+/// A thrown exception of a [`RustdocComment`].
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Throws {
+    /// The name of the exception.
+    pub name: String,
+    /// The description of when the exception is thrown.
+    pub description: String,
+}
+
+/// The visibility of the members documented by a [`RustdocComment`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Visibility {
+    /// `@public` or `@publicsection`.
+    Public,
+    /// `@protected` or `@protectedsection`.
+    Protected,
+    /// `@private` or `@privatesection`.
+    Private,
+}
+
+/// A child page of a [`RustdocComment`].
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct SubPage {
+    /// The name of the page, like `intro`.
+    pub name: String,
+    /// The title of the link to the page, if any.
+    pub title: Option<String>,
+}
+
+impl fmt::Display for RustdocComment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.rendered)
+    }
+}
+
+/// A semantic difference between two versions of a comment, found by
+/// [`RustdocComment::changes`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Change {
+    /// A parameter documented only by the new version.
+    AddedParam(String),
+    /// A parameter documented only by the old version.
+    RemovedParam(String),
+    /// A parameter whose direction or description changed.
+    ChangedParam { old: Param, new: Param },
+    /// A section whose text changed, like `brief` or `returns`. The texts of the sections made of
+    /// several entries (like the return values) have one line per entry.
+    ChangedSection {
+        section: &'static str,
+        old: String,
+        new: String,
+    },
+}
+
+impl RustdocComment {
+    /// Returns the differences between this comment and its `new` version, like the parameters
+    /// added by an update of a library. The texts only differing by their whitespace are the same.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use doxygen_rs::comment::{convert, Change};
+    ///
+    /// let old = convert("@brief Opens a file.\n@param path The path.".into()).unwrap();
+    /// let new = convert("@brief Opens a file.\n@param path The path.\n@param flags The flags.".into())
+    ///     .unwrap();
+    ///
+    /// assert_eq!(old.changes(&new), vec![Change::AddedParam("flags".into())]);
+    /// ```
+    pub fn changes(&self, new: &RustdocComment) -> Vec<Change> {
+        let mut changes = vec![];
+
+        for old_param in &self.params {
+            match new.params.iter().find(|param| param.name == old_param.name) {
+                None => changes.push(Change::RemovedParam(old_param.name.clone())),
+                Some(new_param)
+                    if new_param.direction != old_param.direction
+                        || !same_text(&new_param.description, &old_param.description) =>
+                {
+                    changes.push(Change::ChangedParam {
+                        old: old_param.clone(),
+                        new: new_param.clone(),
+                    })
+                }
+                Some(_) => {}
+            }
+        }
+
+        changes.extend(
+            new.params
+                .iter()
+                .filter(|param| !self.params.iter().any(|old| old.name == param.name))
+                .map(|param| Change::AddedParam(param.name.clone())),
+        );
+
+        let (old_sections, new_sections) = (self.section_texts(), new.section_texts());
+        for ((section, old), (_, new)) in old_sections.into_iter().zip(new_sections) {
+            if !same_text(&old, &new) {
+                changes.push(Change::ChangedSection { section, old, new });
+            }
+        }
+
+        changes
+    }
+
+    /// Returns the texts of the sections compared by [`RustdocComment::changes`], besides the
+    /// parameters.
+    fn section_texts(&self) -> [(&'static str, String); 12] {
+        let lines = |texts: &[String]| texts.join("\n");
+        let entries = |entries: Vec<String>| entries.join("\n");
+
+        [
+            ("brief", self.brief.clone()),
+            ("description", self.description.clone()),
+            ("returns", self.returns.clone().unwrap_or_default()),
+            (
+                "retvals",
+                entries(
+                    self.retvals
+                        .iter()
+                        .map(|retval| format!("{} - {}", retval.value, retval.description))
+                        .collect(),
+                ),
+            ),
+            (
+                "throws",
+                entries(
+                    self.throws
+                        .iter()
+                        .map(|throws| format!("{} - {}", throws.name, throws.description))
+                        .collect(),
+                ),
+            ),
+            ("preconditions", lines(&self.preconditions)),
+            ("postconditions", lines(&self.postconditions)),
+            ("notes", lines(&self.notes)),
+            ("warnings", lines(&self.warnings)),
+            ("important", lines(&self.important)),
+            ("deprecated", self.deprecated.clone().unwrap_or_default()),
+            ("since", self.since.clone().unwrap_or_default()),
+        ]
+    }
+}
+
+/// Returns whether two texts only differ by their whitespace, like a rewrapped paragraph.
+fn same_text(a: &str, b: &str) -> bool {
+    a.split_whitespace().eq(b.split_whitespace())
+}
+
+/// Creates a [`RustdocComment`] from a Doxygen string.
+///
+/// # Errors
+///
+/// See [`generator::rustdoc`].
+pub fn convert(input: String) -> Result<RustdocComment, ParseError> {
+    convert_with_options(input, &Options::default())
+}
+
+/// Creates a [`RustdocComment`] from a Doxygen string, using the given [`Options`].
+///
+/// # Errors
+///
+/// See [`generator::rustdoc_with_options`].
+pub fn convert_with_options(
+    input: String,
+    options: &Options,
+) -> Result<RustdocComment, ParseError> {
+    let mut comment = RustdocComment::default();
+    let parsed = parse_recovering(&input, options, None)?;
+
+    let mut stats = Stats::default();
+    let mut generator = Generator::new(options, &mut stats);
+    let mut section = Section::Description;
+
+    let mut items = parsed.iter().peekable();
+    while let Some(item) = items.next() {
+        match item {
+            GrammarItem::Notation {
+                meta,
+                params,
+                tag,
+                line,
+                span,
+            } => {
+                if tag.is_block_command() {
+                    section = Section::Description;
+                }
+
+                let name = params.first().cloned().unwrap_or_default();
+                section = match tag {
+                    Tag::Brief | Tag::Short => Section::Brief,
+                    Tag::Param => {
+                        comment.params.push(Param {
+                            name: generator::param_name(&name).into(),
+                            direction: parse_direction(meta),
+                            description: String::new(),
+                        });
+                        Section::Param(comment.params.len() - 1)
+                    }
+                    Tag::Returns | Tag::Return | Tag::Result => Section::Returns,
+                    Tag::Retval => {
+                        comment.retvals.push(ReturnValue {
+                            value: name,
+                            description: String::new(),
+                        });
+                        Section::Retval(comment.retvals.len() - 1)
+                    }
+                    Tag::Throw | Tag::Throws | Tag::Exception => {
+                        comment.throws.push(Throws {
+                            name,
+                            description: String::new(),
+                        });
+                        Section::Throws(comment.throws.len() - 1)
+                    }
+                    Tag::Pre => new_entry(&mut comment.preconditions, Section::Precondition),
+                    Tag::Post => new_entry(&mut comment.postconditions, Section::Postcondition),
+                    Tag::Note | Tag::Remark | Tag::Remarks => {
+                        new_entry(&mut comment.notes, Section::Note)
+                    }
+                    Tag::Warning | Tag::Attention => {
+                        new_entry(&mut comment.warnings, Section::Warning)
+                    }
+                    Tag::Important => new_entry(&mut comment.important, Section::Important),
+                    Tag::Deprecated => Section::Deprecated,
+                    Tag::Since => Section::Since,
+                    Tag::Par => Section::Description,
+                    // The references are only listed, not part of the texts
+                    Tag::Sa | Tag::See => {
+                        comment.see_also.extend(params.iter().cloned());
+                        Section::Ignored
+                    }
+                    _ if tag.visibility().is_some() => {
+                        comment.visibility = tag.visibility();
+                        section
+                    }
+                    Tag::Qualifier => {
+                        comment.qualifiers.extend(params.first().cloned());
+                        section
+                    }
+                    Tag::Defgroup | Tag::Addtogroup | Tag::Weakgroup => {
+                        comment.groups.extend(params.first().cloned());
+                        section
+                    }
+                    Tag::Ingroup => {
+                        comment.groups.extend(params.iter().cloned());
+                        section
+                    }
+                    Tag::Subpage => {
+                        comment.subpages.push(SubPage {
+                            name,
+                            title: params.get(1).cloned(),
+                        });
+                        section
+                    }
+                    _ => section,
+                };
+
+                // The paragraphs are part of the description, under their title (if any)
+                if *tag == Tag::Par {
+                    let description = &comment.description;
+                    let text = if !generator::is_untitled(items.peek().copied()) {
+                        generator.notation(tag, meta, params, *line, span, false)?
+                    } else if description.is_empty() || description.ends_with('\n') {
+                        String::new()
+                    } else {
+                        String::from("\n")
+                    };
+                    comment.push(section, &text);
+                }
+
+                // Only the inline notations are part of the texts, the others are sections
+                if (!tag.is_block_command() && !matches!(tag, Tag::Sa | Tag::See))
+                    || matches!(
+                        tag,
+                        Tag::Code | Tag::Verbatim | Tag::Dotfile | Tag::Mscfile | Tag::Diafile
+                    )
+                {
+                    let text = generator.notation(tag, meta, params, *line, span, false)?;
+                    comment.push(section, &text);
+                }
+            }
+            GrammarItem::Text(text) => {
+                let end = match section {
+                    Section::Description => None,
+                    _ => paragraph_end(text),
+                };
+
+                match end {
+                    Some(end) => {
+                        comment.push(section, &text[..end]);
+                        section = Section::Description;
+                        comment.push(section, &text[end..]);
+                    }
+                    None => comment.push(section, text),
+                }
+            }
+            GrammarItem::LinkTarget { target, .. } => {
+                comment.push(section, &generator::link_target(target, options));
+            }
+            GrammarItem::GroupStart | GrammarItem::GroupEnd => {}
+        }
+    }
+
+    comment.trim();
+    comment.merge_retvals();
+    generator::generate_parsed(
+        parsed,
+        input.len(),
+        options,
+        &mut stats,
+        None,
+        &mut comment.rendered,
+    )?;
+
+    Ok(comment)
+}
+
+/// Appends the converted Rustdoc to existing (usually hand-written) Rustdoc, leaving out the
+/// sections whose heading is already in the existing Rustdoc, like a hand-written `# Safety`.
+///
+/// The parts of the converted Rustdoc already in the existing one are left out too, so merging the
+/// same Rustdoc again doesn't change it.
+///
+/// # Examples
+///
+/// ```
+/// use doxygen_rs::comment::merge;
+/// use doxygen_rs::generator::rustdoc_with_options;
+/// use doxygen_rs::options::Options;
+///
+/// let options = Options {
+///     pre_as_safety: true,
+///     ..Default::default()
+/// };
+/// let input = "@brief Frees memory.\n@param ptr The memory.\n@pre Valid.";
+/// let converted = rustdoc_with_options(input.into(), &options).unwrap();
+///
+/// let existing = "Wraps `free`.\n\n# Safety\n\n`ptr` must be valid.";
+///
+/// assert_eq!(
+///     merge(existing, &converted),
+///     "Wraps `free`.\n\n# Safety\n\n`ptr` must be valid.\n\nFrees memory.\n\n# Arguments\n\n* `ptr` - The memory."
+/// );
+/// ```
+pub fn merge(existing: &str, converted: &str) -> String {
+    let existing_headings = split_sections(existing)
+        .into_iter()
+        .filter_map(|(heading, _)| heading)
+        .collect::<Vec<_>>();
+
+    let existing_paragraphs = paragraphs(existing).collect::<HashSet<_>>();
+
+    let mut result = existing.trim_end().to_string();
+
+    for (heading, section) in split_sections(converted) {
+        let is_duplicated = heading.is_some_and(|heading| {
+            existing_headings
+                .iter()
+                .any(|existing| existing.eq_ignore_ascii_case(heading))
+        });
+
+        // Whole paragraphs are compared, as a short section can be part of an unrelated sentence
+        let section = section.trim();
+        let is_merged =
+            paragraphs(section).all(|paragraph| existing_paragraphs.contains(paragraph));
+        if is_duplicated || section.is_empty() || is_merged {
+            continue;
+        }
+
+        if !result.is_empty() {
+            result += "\n\n";
+        }
+        result += section;
+    }
+
+    result
+}
+
+/// Returns the trimmed paragraphs of Rustdoc, separated by blank lines.
+fn paragraphs(rustdoc: &str) -> impl Iterator<Item = &str> {
+    rustdoc
+        .split("\n\n")
+        .map(str::trim)
+        .filter(|paragraph| !paragraph.is_empty())
+}
+
+/// Splits Rustdoc into its sections, starting at each heading (or bold label, like
+/// `**Arguments:**`), returning the title of the heading with the section.
+fn split_sections(rustdoc: &str) -> Vec<(Option<&str>, String)> {
+    let mut sections = vec![(None, String::new())];
+    let mut in_code_block = false;
+
+    for line in rustdoc.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+        }
+
+        let title = if in_code_block {
+            None
+        } else if let Some(heading) = line.strip_prefix('#') {
+            Some(heading.trim_start_matches('#').trim())
+        } else {
+            line.strip_prefix("**")
+                .and_then(|label| label.strip_suffix(":**"))
+        };
+
+        if title.is_some() {
+            sections.push((title, String::new()));
+        }
+
+        let (_, section) = sections.last_mut().unwrap();
+        *section += line;
+        section.push('\n');
+    }
+
+    sections
+}
+
+/// The section of a [`RustdocComment`] the text goes to.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Section {
+    Brief,
+    Description,
+    Param(usize),
+    Returns,
+    Retval(usize),
+    Throws(usize),
+    Precondition(usize),
+    Postcondition(usize),
+    Note(usize),
+    Warning(usize),
+    Important(usize),
+    Deprecated,
+    Since,
+    Ignored,
+}
+
+impl RustdocComment {
+    fn push(&mut self, section: Section, text: &str) {
+        let target = match section {
+            Section::Brief => &mut self.brief,
+            Section::Description => &mut self.description,
+            Section::Param(index) => &mut self.params[index].description,
+            Section::Returns => self.returns.get_or_insert_with(String::new),
+            Section::Retval(index) => &mut self.retvals[index].description,
+            Section::Throws(index) => &mut self.throws[index].description,
+            Section::Precondition(index) => &mut self.preconditions[index],
+            Section::Postcondition(index) => &mut self.postconditions[index],
+            Section::Note(index) => &mut self.notes[index],
+            Section::Warning(index) => &mut self.warnings[index],
+            Section::Important(index) => &mut self.important[index],
+            Section::Deprecated => self.deprecated.get_or_insert_with(String::new),
+            Section::Since => self.since.get_or_insert_with(String::new),
+            Section::Ignored => return,
+        };
+
+        *target += text;
+    }
+
+    /// Trims the whitespace around the texts of the sections.
+    fn trim(&mut self) {
+        let texts = [&mut self.brief, &mut self.description]
+            .into_iter()
+            .chain(self.returns.as_mut())
+            .chain(self.deprecated.as_mut())
+            .chain(self.since.as_mut())
+            .chain(self.params.iter_mut().map(|param| &mut param.description))
+            .chain(
+                self.retvals
+                    .iter_mut()
+                    .map(|retval| &mut retval.description),
+            )
+            .chain(self.throws.iter_mut().map(|throws| &mut throws.description))
+            .chain(self.preconditions.iter_mut())
+            .chain(self.postconditions.iter_mut())
+            .chain(self.notes.iter_mut())
+            .chain(self.warnings.iter_mut())
+            .chain(self.important.iter_mut());
+
+        for text in texts {
+            *text = text.trim().to_string();
+        }
+    }
+
+    /// Merges the descriptions of the repeated return values, like in the rendered Rustdoc.
+    fn merge_retvals(&mut self) {
+        let mut merged: Vec<(String, Vec<String>)> = Vec::with_capacity(self.retvals.len());
+        // The index in `merged` of each value
+        let mut indices: HashMap<String, usize> = HashMap::with_capacity(self.retvals.len());
+
+        for ReturnValue { value, description } in std::mem::take(&mut self.retvals) {
+            let index = *indices.entry(value.clone()).or_insert_with(|| {
+                merged.push((value, vec![]));
+                merged.len() - 1
+            });
+
+            let descriptions = &mut merged[index].1;
+            if !description.is_empty() && !descriptions.contains(&description) {
+                descriptions.push(description);
+            }
+        }
+
+        self.retvals = merged
+            .into_iter()
+            .map(|(value, descriptions)| ReturnValue {
+                value,
+                description: descriptions.join(" "),
+            })
+            .collect();
+    }
+}
+
+/// Adds an empty entry to the texts, returning the section of the new entry.
+fn new_entry(texts: &mut Vec<String>, section: fn(usize) -> Section) -> Section {
+    texts.push(String::new());
+    section(texts.len() - 1)
+}
+
+fn parse_direction(meta: &[String]) -> Option<Direction> {
+    let is_in = meta.iter().any(|direction| direction == "in");
+    let is_out = meta.iter().any(|direction| direction == "out");
+
+    match (is_in, is_out) {
+        (true, true) => Some(Direction::InOut),
+        (true, false) => Some(Direction::In),
+        (false, true) => Some(Direction::Out),
+        (false, false) => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sections() {
+        let comment = convert(
+            "@brief Opens a file.\n\nDetails with @b bold, see @sa close\n@param[out] handle The handle.\n@param path\n@returns Zero on success.\n@retval -1 Failure.\n@pre @c path exists.\n@note Slow.\n@warning Leaks.\n@important Once.".into(),
+        )
+        .unwrap();
+
+        assert_eq!(comment.brief, "Opens a file.");
+        assert_eq!(comment.description, "Details with **bold**, see");
+        assert_eq!(
+            comment.params,
+            vec![
+                Param {
+                    name: "handle".into(),
+                    direction: Some(Direction::Out),
+                    description: "The handle.".into(),
+                },
+                Param {
+                    name: "path".into(),
+                    direction: None,
+                    description: "".into(),
+                },
+            ]
+        );
+        assert_eq!(comment.returns.as_deref(), Some("Zero on success."));
+        assert_eq!(
+            comment.retvals,
+            vec![ReturnValue {
+                value: "-1".into(),
+                description: "Failure.".into(),
+            }]
+        );
+        assert_eq!(comment.preconditions, vec!["`path` exists."]);
+        assert_eq!(comment.notes, vec!["Slow."]);
+        assert_eq!(comment.warnings, vec!["Leaks."]);
+        assert_eq!(comment.important, vec!["Once."]);
+        assert_eq!(comment.see_also, vec!["close"]);
+    }
+
+    #[test]
+    fn multiline_brief() {
+        let comment = convert("@brief Opens\na file.\n\nDetails.".into()).unwrap();
+        assert_eq!(comment.brief, "Opens\na file.");
+        assert_eq!(comment.description, "Details.");
+    }
+
+    #[test]
+    fn repeated_return_values() {
+        let comment = convert(
+            "@retval 0 Success.\n@retval -1 Failure.\n\nMore details.\n@retval 0 Success.\n@retval -1 Out of memory.".into(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            comment.retvals,
+            vec![
+                ReturnValue {
+                    value: "0".into(),
+                    description: "Success.".into(),
+                },
+                ReturnValue {
+                    value: "-1".into(),
+                    description: "Failure. Out of memory.".into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn see_also_after_sections() {
+        let comment = convert("@param a x\n@sa b".into()).unwrap();
+        assert_eq!(comment.params[0].description, "x");
+        assert_eq!(comment.see_also, vec!["b"]);
+
+        let comment = convert("@brief B\n@see c".into()).unwrap();
+        assert_eq!(comment.brief, "B");
+        assert_eq!(comment.see_also, vec!["c"]);
+        assert!(comment.to_string().contains("# See also"));
+    }
+
+    #[test]
+    fn paragraphs() {
+        let comment = convert("@par Title\nContent text.".into()).unwrap();
+        assert_eq!(comment.description, "# Title\nContent text.");
+        assert_eq!(comment.to_string(), "# Title\nContent text.");
+
+        let comment = convert("Intro.\n@par\nUntitled.\n@param a The value.".into()).unwrap();
+        assert_eq!(comment.description, "Intro.\n\nUntitled.");
+        assert_eq!(comment.params[0].description, "The value.");
+    }
+
+    #[test]
+    fn deprecation() {
+        let comment = convert("@brief Old.\n@deprecated Use @c new.\n@since 1.2".into()).unwrap();
+        assert_eq!(comment.deprecated.as_deref(), Some("Use `new`."));
+        assert_eq!(comment.since.as_deref(), Some("1.2"));
+    }
+
+    #[test]
+    fn qualifiers() {
+        let comment = convert("@qualifier static\n@qualifier \"(thread safe)\" Logs.".into());
+        let comment = comment.unwrap();
+
+        assert_eq!(comment.qualifiers, vec!["static", "(thread safe)"]);
+        assert_eq!(comment.to_string(), "Logs.");
+    }
+
+    #[test]
+    fn groups() {
+        let comment =
+            convert("@defgroup io Input and output\n@addtogroup files\nThe files.".into());
+        let comment = comment.unwrap();
+
+        assert_eq!(comment.groups, vec!["io", "files"]);
+        assert_eq!(comment.to_string(), "The files.");
+
+        let comment = convert("Reads.\n@ingroup io files\n@ingroup k".into()).unwrap();
+        assert_eq!(comment.groups, vec!["io", "files", "k"]);
+        assert_eq!(comment.to_string(), "Reads.");
+    }
+
+    #[test]
+    fn visibility() {
+        let comment = convert("@brief Internal.\n@privatesection".into()).unwrap();
+        assert_eq!(comment.visibility, Some(Visibility::Private));
+        assert_eq!(comment.to_string(), "Internal.");
+    }
+
+    #[test]
+    fn subpages() {
+        let comment = convert("Chapters:\n@subpage intro \"Introduction\"\n@subpage usage".into());
+
+        assert_eq!(
+            comment.unwrap().subpages,
+            vec![
+                SubPage {
+                    name: "intro".into(),
+                    title: Some("Introduction".into()),
+                },
+                SubPage {
+                    name: "usage".into(),
+                    title: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn merges() {
+        let converted = "Brief.\n\n# Arguments\n\n* `x` - X.\n\n**Safety:**\n\nUnsafe.";
+
+        assert_eq!(merge("", converted), converted);
+        assert_eq!(merge(converted, converted), converted);
+        assert_eq!(
+            merge("Hand-written.\n\n# safety\n\nAlso unsafe.", converted),
+            "Hand-written.\n\n# safety\n\nAlso unsafe.\n\nBrief.\n\n# Arguments\n\n* `x` - X."
+        );
+        assert_eq!(
+            merge("```\n# Arguments\n```", converted),
+            format!("```\n# Arguments\n```\n\n{converted}")
+        );
+        assert_eq!(merge("See a.", "a"), "See a.\n\na");
+        assert_eq!(merge("See a.\n\na", "a"), "See a.\n\na");
+    }
+
+    #[cfg(feature = "syn")]
+    #[test]
+    fn checks_signatures() {
+        let comment =
+            convert("@param type The type.\n@param ... The values.\n@retval 0 Success.".into())
+                .unwrap();
+
+        let signature =
+            syn::parse_str("unsafe extern \"C\" fn log(type_: i32, ...) -> i32").unwrap();
+        assert_eq!(comment.check_signature(&signature), vec![]);
+
+        let signature = syn::parse_str("fn log(&self, r#type: i32, level: i32) -> !").unwrap();
+        assert_eq!(
+            comment.check_signature(&signature),
+            vec![
+                SignatureLint::UnknownParam("...".into()),
+                SignatureLint::UndocumentedParam("level".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn changes() {
+        let old = convert(
+            "@brief Reads.\n@param[in] fd The file.\n@param size The size.\n@retval 0 Success."
+                .into(),
+        )
+        .unwrap();
+        let new = convert(
+            "@brief Reads.\n@param[in,out] fd The file.\n@param buffer The buffer.\n@retval 0 Success.\n@retval -1 Failure.\n@deprecated Use `read2`."
+                .into(),
+        )
+        .unwrap();
+
+        assert_eq!(old.changes(&old), vec![]);
+        assert_eq!(
+            old.changes(&new),
+            vec![
+                Change::ChangedParam {
+                    old: old.params[0].clone(),
+                    new: new.params[0].clone(),
+                },
+                Change::RemovedParam("size".into()),
+                Change::AddedParam("buffer".into()),
+                Change::ChangedSection {
+                    section: "retvals",
+                    old: "0 - Success.".into(),
+                    new: "0 - Success.\n-1 - Failure.".into(),
+                },
+                Change::ChangedSection {
+                    section: "deprecated",
+                    old: "".into(),
+                    new: "Use `read2`.".into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn renders_rustdoc() {
+        let input = "@brief Example\n@param x The value.\n@throws std::bad_alloc Out of memory.";
+        let comment = convert(input.into()).unwrap();
+
+        assert_eq!(comment.throws[0].name, "std::bad_alloc");
+        assert_eq!(comment.throws[0].description, "Out of memory.");
+        assert_eq!(
+            comment.to_string(),
+            generator::rustdoc(input.into()).unwrap()
+        );
+    }
+}
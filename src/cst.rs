@@ -0,0 +1,541 @@
+//! Lossless concrete syntax tree of Doxygen comments.
+//!
+//! Unlike the translation to Rustdoc, the tree keeps every character of the comment (whitespace,
+//! comment decorations like the leading `*`, unknown commands), so tools like formatters can
+//! rewrite parts of a comment without disturbing the rest of it.
+
+use std::fmt;
+use std::ops::Range;
+
+use crate::parser::is_command;
+use crate::tag::Tag;
+
+/// The kind of a [`Token`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum TokenKind {
+    /// A command with its prefix, like `@param[in]` or `\brief`.
+    Command,
+    /// The start of a group, `@{`.
+    GroupStart,
+    /// The end of a group, `@}`.
+    GroupEnd,
+    /// Any other text without whitespace.
+    Word,
+    /// Spaces and tabs.
+    Whitespace,
+    /// A line ending, including its `\r` (if any).
+    NewLine,
+    /// The comment markers around and at the start of the lines, like `/**`, `*` or `///`.
+    Decoration,
+}
+
+/// A piece of the source, the leaves of the tree.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Token {
+    pub kind: TokenKind,
+    /// The bytes of the token in the source.
+    pub range: Range<usize>,
+}
+
+/// The kind of a [`Node`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum NodeKind {
+    /// Text between the commands, including its whitespace and decorations.
+    Text,
+    /// A command and its arguments, like `@param[in] name` or `@c word`.
+    Command {
+        /// The name of the command, like `param`.
+        name: String,
+        /// The indices of the tokens of the arguments.
+        arguments: Vec<usize>,
+    },
+    /// The start of a group, `@{`.
+    GroupStart,
+    /// The end of a group, `@}`.
+    GroupEnd,
+}
+
+/// A typed group of consecutive tokens.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Node {
+    pub kind: NodeKind,
+    /// The indices of the tokens of the node.
+    pub tokens: Range<usize>,
+}
+
+/// The concrete syntax tree of a Doxygen comment: the tokens covering all the source, with the
+/// nodes covering all the tokens.
+///
+/// # Examples
+///
+/// ```
+/// use doxygen_rs::cst::{Cst, NodeKind};
+///
+/// let mut cst = Cst::parse("/**\n * @brief Example\n * @param a The  value\n */");
+///
+/// let param = cst
+///     .nodes()
+///     .iter()
+///     .position(|node| matches!(&node.kind, NodeKind::Command { name, .. } if name == "param"))
+///     .unwrap();
+/// assert_eq!(cst.node_text(param), "@param a");
+///
+/// cst.replace(param, "@param[in] a");
+/// assert_eq!(cst.to_string(), "/**\n * @brief Example\n * @param[in] a The  value\n */");
+/// ```
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Cst {
+    source: String,
+    tokens: Vec<Token>,
+    nodes: Vec<Node>,
+}
+
+impl Cst {
+    /// Parses the source of a comment, which can include its decorations.
+    pub fn parse(source: impl Into<String>) -> Self {
+        let source = source.into();
+        let tokens = tokenize(&source);
+        let nodes = group(&source, &tokens);
+
+        Self {
+            source,
+            tokens,
+            nodes,
+        }
+    }
+
+    /// The source of the comment.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// The tokens, in the order of the source.
+    pub fn tokens(&self) -> &[Token] {
+        &self.tokens
+    }
+
+    /// The nodes, in the order of the source.
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    /// The source of the token at `index`.
+    pub fn token_text(&self, index: usize) -> &str {
+        &self.source[self.tokens[index].range.clone()]
+    }
+
+    /// The source of the node at `index`.
+    pub fn node_text(&self, index: usize) -> &str {
+        &self.source[self.node_range(index)]
+    }
+
+    /// The bytes of the node at `index` in the source.
+    pub fn node_range(&self, index: usize) -> Range<usize> {
+        let tokens = &self.nodes[index].tokens;
+
+        match (self.tokens.get(tokens.start), tokens.end.checked_sub(1)) {
+            (Some(first), Some(last)) => first.range.start..self.tokens[last].range.end,
+            _ => 0..0,
+        }
+    }
+
+    /// Replaces the source of the node at `index` with `text`, keeping the rest of the source as it
+    /// is. See [`Cst::edit`].
+    pub fn replace(&mut self, index: usize, text: &str) -> Range<usize> {
+        self.edit(self.node_range(index), text)
+    }
+
+    /// Replaces the bytes `range` of the source with `text`, like an edit made in an editor.
+    ///
+    /// Only the lines touched by the edit are tokenized again, and only the nodes around them are
+    /// grouped again, so editing a large comment stays cheap. The result is the same as parsing
+    /// the edited source from scratch. Returns the indices of the nodes that were grouped again;
+    /// the indices of the following nodes shift by the difference in the number of nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use doxygen_rs::cst::Cst;
+    ///
+    /// let mut cst = Cst::parse("@brief Example\nSome @c text\nEnd");
+    /// let changed = cst.edit(21..22, "b");
+    ///
+    /// assert_eq!(cst.source(), "@brief Example\nSome @b text\nEnd");
+    /// assert_eq!(cst, Cst::parse(cst.source()));
+    /// assert_eq!(cst.node_text(changed.start + 1), "@b text");
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is out of bounds or doesn't lie on UTF-8 character boundaries.
+    pub fn edit(&mut self, range: Range<usize>, text: &str) -> Range<usize> {
+        // The lines touched by the edit, which always start and end on token boundaries
+        let line_start = self.source[..range.start]
+            .rfind('\n')
+            .map_or(0, |index| index + 1);
+        let line_end = self.source[range.end..]
+            .find('\n')
+            .map_or(self.source.len(), |index| range.end + index + 1);
+
+        self.source.replace_range(range.clone(), text);
+        let delta = text.len() as isize - range.len() as isize;
+        let new_line_end = (line_end as isize + delta) as usize;
+
+        // Tokenizes the lines again
+        let first_token = self
+            .tokens
+            .partition_point(|token| token.range.start < line_start);
+        let last_token = self
+            .tokens
+            .partition_point(|token| token.range.start < line_end);
+
+        let new_tokens = tokenize(&self.source[line_start..new_line_end])
+            .into_iter()
+            .map(|token| Token {
+                kind: token.kind,
+                range: token.range.start + line_start..token.range.end + line_start,
+            })
+            .collect::<Vec<_>>();
+        let token_delta = new_tokens.len() as isize - (last_token - first_token) as isize;
+        let new_last_token = new_tokens.len() + first_token;
+
+        self.tokens.splice(first_token..last_token, new_tokens);
+        for token in &mut self.tokens[new_last_token..] {
+            token.range = shift(token.range.start, delta)..shift(token.range.end, delta);
+        }
+
+        // Groups the nodes of the tokens again, starting and ending next to nodes that aren't
+        // text, as consecutive text is merged into a single node
+        let mut first_node = self
+            .nodes
+            .partition_point(|node| node.tokens.end <= first_token);
+        if first_node > 0 && self.nodes[first_node - 1].kind == NodeKind::Text {
+            first_node -= 1;
+        }
+
+        let mut last_node = self
+            .nodes
+            .partition_point(|node| node.tokens.start < last_token.max(first_token + 1));
+        if self
+            .nodes
+            .get(last_node)
+            .is_some_and(|node| node.kind == NodeKind::Text)
+        {
+            last_node += 1;
+        }
+
+        for node in &mut self.nodes[last_node..] {
+            node.tokens =
+                shift(node.tokens.start, token_delta)..shift(node.tokens.end, token_delta);
+
+            if let NodeKind::Command { arguments, .. } = &mut node.kind {
+                for argument in arguments {
+                    *argument = shift(*argument, token_delta);
+                }
+            }
+        }
+
+        let start = self
+            .nodes
+            .get(first_node)
+            .map_or(0, |node| node.tokens.start);
+        let end = match self.nodes.get(last_node) {
+            Some(node) => node.tokens.start,
+            None => self.tokens.len(),
+        };
+
+        let new_nodes = group(&self.source, &self.tokens[start..end])
+            .into_iter()
+            .map(|mut node| {
+                node.tokens = node.tokens.start + start..node.tokens.end + start;
+
+                if let NodeKind::Command { arguments, .. } = &mut node.kind {
+                    for argument in arguments {
+                        *argument += start;
+                    }
+                }
+
+                node
+            })
+            .collect::<Vec<_>>();
+
+        let changed = first_node..first_node + new_nodes.len();
+        self.nodes.splice(first_node..last_node, new_nodes);
+
+        changed
+    }
+}
+
+impl fmt::Display for Cst {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+fn shift(value: usize, delta: isize) -> usize {
+    (value as isize + delta) as usize
+}
+
+/// Splits the source into tokens, without leaving any character out.
+fn tokenize(source: &str) -> Vec<Token> {
+    let mut tokens = vec![];
+    let mut index = 0;
+    let mut line_start = true;
+
+    while index < source.len() {
+        let rest = &source[index..];
+        let c = rest.chars().next().unwrap_or_default();
+
+        let (kind, length) = if c == '\n' || rest.starts_with("\r\n") {
+            (TokenKind::NewLine, if c == '\n' { 1 } else { 2 })
+        } else if c.is_whitespace() {
+            // Any whitespace but the new lines, like a lone `\r` or U+3000
+            let mut length = rest
+                .find(|c: char| !c.is_whitespace() || c == '\n')
+                .unwrap_or(rest.len());
+            if rest[..length].ends_with('\r') && rest[length..].starts_with('\n') {
+                length -= 1;
+            }
+            (TokenKind::Whitespace, length)
+        } else if let Some(length) = decoration(rest, line_start) {
+            (TokenKind::Decoration, length)
+        } else if rest.starts_with("@{") || rest.starts_with("\\{") {
+            (TokenKind::GroupStart, 2)
+        } else if rest.starts_with("@}") || rest.starts_with("\\}") {
+            (TokenKind::GroupEnd, 2)
+        } else {
+            let length = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            let word = &rest[..length];
+
+            match word.strip_prefix(['@', '\\']) {
+                Some(command) if is_command(command) => (TokenKind::Command, length),
+                _ => (TokenKind::Word, length),
+            }
+        };
+
+        assert!(length > 0, "empty token at {index}");
+        line_start = kind == TokenKind::NewLine || (line_start && kind == TokenKind::Whitespace);
+        tokens.push(Token {
+            kind,
+            range: index..index + length,
+        });
+        index += length;
+    }
+
+    tokens
+}
+
+/// Returns the length of the comment decoration at the start of the text (if any). Only the end
+/// of the comment, `*/`, can be found outside of the start of the lines.
+fn decoration(text: &str, line_start: bool) -> Option<usize> {
+    let decorated = |marker: &str| {
+        text.strip_prefix(marker)
+            .filter(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace))
+            .map(|_| marker.len())
+    };
+
+    if line_start {
+        ["/**", "/*!", "///<", "///", "//!<", "//!", "*/", "*"]
+            .into_iter()
+            .find_map(decorated)
+    } else {
+        decorated("*/")
+    }
+}
+
+/// Groups the tokens into nodes, giving the commands the tokens of their arguments.
+fn group(source: &str, tokens: &[Token]) -> Vec<Node> {
+    let mut nodes: Vec<Node> = vec![];
+    let mut index = 0;
+
+    while index < tokens.len() {
+        let token = &tokens[index];
+        let (kind, end) = match token.kind {
+            TokenKind::Command => {
+                let name = &source[token.range.start + 1..token.range.end];
+                let (name, direction) = name.split_at(name.find('[').unwrap_or(name.len()));
+                let arguments = arguments(source, tokens, index + 1, name, direction);
+                let end = arguments.last().map_or(index + 1, |last| last + 1);
+
+                let kind = NodeKind::Command {
+                    name: name.into(),
+                    arguments,
+                };
+
+                (kind, end)
+            }
+            TokenKind::GroupStart => (NodeKind::GroupStart, index + 1),
+            TokenKind::GroupEnd => (NodeKind::GroupEnd, index + 1),
+            _ => {
+                match nodes.last_mut() {
+                    Some(node) if node.kind == NodeKind::Text => node.tokens.end = index + 1,
+                    _ => nodes.push(Node {
+                        kind: NodeKind::Text,
+                        tokens: index..index + 1,
+                    }),
+                }
+
+                index += 1;
+                continue;
+            }
+        };
+
+        nodes.push(Node {
+            kind,
+            tokens: index..end,
+        });
+        index = end;
+    }
+
+    nodes
+}
+
+/// Returns the indices of the argument tokens of the command named `name`, which start at the
+/// token `from`. `direction` is the direction attached to the command, like `[in]` in `@param[in]`.
+fn arguments(
+    source: &str,
+    tokens: &[Token],
+    from: usize,
+    name: &str,
+    direction: &str,
+) -> Vec<usize> {
+    let tag = Tag::from(name);
+    if !matches!(tag, Tag::Param | Tag::Ref) && !tag.takes_word() {
+        return vec![];
+    }
+
+    // The arguments are the words following the command on the same line
+    let mut words = tokens[from..]
+        .iter()
+        .enumerate()
+        .take_while(|(_, token)| matches!(token.kind, TokenKind::Word | TokenKind::Whitespace))
+        .filter(|(_, token)| token.kind == TokenKind::Word)
+        .map(|(index, token)| (from + index, &source[token.range.clone()]))
+        .peekable();
+
+    let mut arguments = vec![];
+    let mut in_direction = tag == Tag::Param
+        && direction.is_empty()
+        && words.peek().is_some_and(|(_, word)| word.starts_with('['));
+
+    for (index, word) in words {
+        arguments.push(index);
+
+        // The references of a `@see` are all the words up to the end of the line
+        if in_direction {
+            in_direction = !word.contains(']');
+        } else if !matches!(tag, Tag::Sa | Tag::See) {
+            break;
+        }
+    }
+
+    arguments
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn kinds(cst: &Cst) -> Vec<TokenKind> {
+        cst.tokens().iter().map(|token| token.kind).collect()
+    }
+
+    #[test]
+    fn keeps_every_character() {
+        let source = "/**\r\n *  @brief Example\t@c\n * \\unknown @{ text @}\n */";
+        let cst = Cst::parse(source);
+
+        assert_eq!(cst.to_string(), source);
+        assert_eq!(
+            cst.tokens()
+                .iter()
+                .map(|token| &source[token.range.clone()])
+                .collect::<String>(),
+            source
+        );
+        assert_eq!(
+            kinds(&Cst::parse(" * @brief a*b */")),
+            [
+                TokenKind::Whitespace,
+                TokenKind::Decoration,
+                TokenKind::Whitespace,
+                TokenKind::Command,
+                TokenKind::Whitespace,
+                TokenKind::Word,
+                TokenKind::Whitespace,
+                TokenKind::Decoration,
+            ]
+        );
+    }
+
+    #[test]
+    fn unusual_whitespace() {
+        assert_eq!(
+            kinds(&Cst::parse("a\rb")),
+            [TokenKind::Word, TokenKind::Whitespace, TokenKind::Word]
+        );
+        assert_eq!(
+            kinds(&Cst::parse("a\u{3000}\x0b\x0c@c b \r\n")),
+            [
+                TokenKind::Word,
+                TokenKind::Whitespace,
+                TokenKind::Command,
+                TokenKind::Whitespace,
+                TokenKind::Word,
+                TokenKind::Whitespace,
+                TokenKind::NewLine,
+            ]
+        );
+
+        let mut cst = Cst::parse("a b");
+        cst.edit(1..2, "\r");
+        assert_eq!(cst.to_string(), "a\rb");
+    }
+
+    #[test]
+    fn groups_commands() {
+        let cst = Cst::parse("Text @param [ in ] a The value @unknown @c word\n@{");
+        let nodes = cst
+            .nodes()
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (node.kind.clone(), cst.node_text(index)))
+            .collect::<Vec<_>>();
+
+        assert_eq!(nodes[0], (NodeKind::Text, "Text "));
+        assert_eq!(
+            nodes[1],
+            (
+                NodeKind::Command {
+                    name: "param".into(),
+                    arguments: vec![4, 6, 8, 10],
+                },
+                "@param [ in ] a"
+            )
+        );
+        assert_eq!(nodes[2], (NodeKind::Text, " The value "));
+        assert!(matches!(&nodes[3].0, NodeKind::Command { name, .. } if name == "unknown"));
+        assert_eq!(nodes[5].1, "@c word");
+        assert_eq!(nodes[7], (NodeKind::GroupStart, "@{"));
+        assert_eq!(Cst::parse("@sa a, b\nc").node_text(0), "@sa a, b");
+    }
+
+    #[test]
+    fn edits_incrementally() {
+        let mut cst = Cst::parse("/**\n * @brief Example\n * @param[in] a The value\n * @{\n */");
+        let edits = [
+            (0..0, "Start "),
+            (13..19, "\\c"),
+            (20..27, "text\n * more"),
+            (44..45, "@"),
+            (10..40, ""),
+            (0..0, "@c word @b @{"),
+            (7..8, "\n"),
+        ];
+
+        for (range, text) in edits {
+            cst.edit(range, text);
+            assert_eq!(cst, Cst::parse(cst.source()), "{:?}", cst.source());
+        }
+    }
+}
@@ -0,0 +1,163 @@
+//! Unified diffs of the edits made to a source.
+
+use crate::transform::Edit;
+
+/// The number of unchanged lines shown around the changes.
+const CONTEXT: usize = 3;
+
+/// The lines changed by an edit, and the lines replacing them.
+struct Change {
+    first_line: usize,
+    last_line: usize,
+    lines: Vec<String>,
+}
+
+/// Creates the unified diff of the edits (which must be sorted and not overlap) to the source,
+/// labeling the file as `path`. Returns an empty string if there are no edits.
+pub(crate) fn unified_diff(path: &str, source: &str, edits: &[Edit]) -> String {
+    if edits.is_empty() {
+        return String::new();
+    }
+
+    let line_starts = source
+        .match_indices('\n')
+        .map(|(index, _)| index + 1)
+        .collect::<Vec<_>>();
+    let line_of = |offset: usize| line_starts.partition_point(|start| *start <= offset);
+
+    let old_lines = source.lines().collect::<Vec<_>>();
+    let changes = edits.iter().map(|edit| {
+        let first_line = line_of(edit.range.start);
+        let last_line = line_of(edit.range.end.saturating_sub(1).max(edit.range.start));
+
+        let line_start = first_line
+            .checked_sub(1)
+            .map_or(0, |line| line_starts[line]);
+        let line_end = line_starts
+            .get(last_line)
+            .map_or(source.len(), |start| start - 1);
+
+        let replaced = format!(
+            "{}{}{}",
+            &source[line_start..edit.range.start],
+            edit.replacement,
+            &source[edit.range.end..line_end]
+        );
+
+        Change {
+            first_line,
+            last_line,
+            lines: replaced.split('\n').map(String::from).collect(),
+        }
+    });
+
+    let mut result = format!("--- a/{path}\n+++ b/{path}\n");
+    let mut hunk: Vec<Change> = vec![];
+    let mut offset = 0isize;
+
+    for change in changes {
+        let is_apart = hunk
+            .last()
+            .is_some_and(|last| change.first_line > last.last_line + 2 * CONTEXT + 1);
+
+        if is_apart {
+            offset += render_hunk(&mut result, &old_lines, &hunk, offset);
+            hunk.clear();
+        }
+
+        hunk.push(change);
+    }
+
+    render_hunk(&mut result, &old_lines, &hunk, offset);
+
+    result
+}
+
+/// Renders the hunk of the changes, returning the difference of lines it makes.
+fn render_hunk(
+    result: &mut String,
+    old_lines: &[&str],
+    changes: &[Change],
+    offset: isize,
+) -> isize {
+    let (Some(first), Some(last)) = (changes.first(), changes.last()) else {
+        return 0;
+    };
+
+    let start = first.first_line.saturating_sub(CONTEXT);
+    let end = (last.last_line + CONTEXT).min(old_lines.len().saturating_sub(1));
+
+    let mut lines = String::new();
+    let mut line = start;
+    let (mut old_count, mut new_count) = (0, 0);
+
+    for change in changes {
+        for context in &old_lines[line..change.first_line] {
+            lines += &format!(" {context}\n");
+        }
+
+        for removed in &old_lines[change.first_line..=change.last_line] {
+            lines += &format!("-{removed}\n");
+        }
+
+        for added in &change.lines {
+            lines += &format!("+{added}\n");
+        }
+
+        let context = change.first_line - line;
+        old_count += context + change.last_line - change.first_line + 1;
+        new_count += context + change.lines.len();
+        line = change.last_line + 1;
+    }
+
+    for context in old_lines.get(line..=end).unwrap_or_default() {
+        lines += &format!(" {context}\n");
+        old_count += 1;
+        new_count += 1;
+    }
+
+    let new_start = start as isize + 1 + offset;
+    *result += &format!(
+        "@@ -{},{old_count} +{new_start},{new_count} @@\n{lines}",
+        start + 1
+    );
+
+    new_count as isize - old_count as isize
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn edit(source: &str, old: &str, new: &str) -> Edit {
+        let start = source.find(old).unwrap();
+
+        Edit {
+            range: start..start + old.len(),
+            replacement: new.into(),
+        }
+    }
+
+    #[test]
+    fn diffs_edits() {
+        let source = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\n";
+        let edits = [edit(source, "b", "B\nB"), edit(source, "k\nl", "K")];
+
+        assert_eq!(
+            unified_diff("x.rs", source, &edits),
+            "--- a/x.rs\n+++ b/x.rs\n@@ -1,5 +1,6 @@\n a\n-b\n+B\n+B\n c\n d\n e\n@@ -8,5 +9,4 @@\n h\n i\n j\n-k\n-l\n+K\n"
+        );
+    }
+
+    #[test]
+    fn merges_close_edits() {
+        let source = "a\nb\nc\nd\n";
+        let edits = [edit(source, "a", "A"), edit(source, "d", "D")];
+
+        assert_eq!(
+            unified_diff("x.rs", source, &edits),
+            "--- a/x.rs\n+++ b/x.rs\n@@ -1,4 +1,4 @@\n-a\n+A\n b\n c\n-d\n+D\n"
+        );
+        assert_eq!(unified_diff("x.rs", source, &[]), "");
+    }
+}
@@ -0,0 +1,477 @@
+//! Post-processing passes over the generated Rustdoc.
+
+/// Normalizes the whitespace of the generated Rustdoc.
+///
+/// Trailing spaces are removed, runs of more than two blank lines are collapsed and the leading blank
+/// lines and trailing whitespace of the whole text are trimmed. The indentation of the first line is
+/// kept, like the one of a list item. Code blocks are kept as-is.
+pub(crate) fn normalize_whitespace(input: &str) -> String {
+    let mut result = String::with_capacity(input.len());
+    let mut in_code_block = false;
+    let mut blank_lines = 0;
+
+    let input = input.trim_end();
+    let leading = &input[..input.len() - input.trim_start().len()];
+    let start = leading.rfind('\n').map_or(0, |index| index + 1);
+
+    for line in input[start..].split('\n') {
+        let is_fence = line.trim_start().starts_with("```");
+
+        if in_code_block && !is_fence {
+            result += line;
+            result.push('\n');
+            continue;
+        }
+
+        if is_fence {
+            in_code_block = !in_code_block;
+        }
+
+        let line = line.trim_end();
+        if line.is_empty() {
+            blank_lines += 1;
+            if blank_lines > 2 {
+                continue;
+            }
+        } else {
+            blank_lines = 0;
+        }
+
+        result += line;
+        result.push('\n');
+    }
+
+    result.pop();
+    result
+}
+
+/// Wraps the lines longer than `width` columns at the spaces between words.
+///
+/// Headings and code blocks are kept as-is, while block quotes and list items keep their prefix
+/// (or an indentation of the same width) on the wrapped lines. The wrapped lines of list items are
+/// indented by `list_indent` spaces instead, if given.
+pub(crate) fn wrap(input: &str, width: usize, list_indent: Option<usize>) -> String {
+    let mut result = String::with_capacity(input.len());
+    let mut in_code_block = false;
+
+    for (index, line) in input.split('\n').enumerate() {
+        if index > 0 {
+            result.push('\n');
+        }
+
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+        }
+
+        if in_code_block || line.starts_with('#') || line.chars().count() <= width {
+            result += line;
+            continue;
+        }
+
+        let (prefix, continuation, content) = split_prefix(line, list_indent);
+        let mut current = prefix.to_string();
+        let mut current_width = prefix.chars().count();
+        let mut is_empty = true;
+
+        for word in content.split(' ').filter(|word| !word.is_empty()) {
+            let word_width = word.chars().count();
+
+            // The words starting a block, like a list item, are kept on the previous line
+            if !is_empty && current_width + 1 + word_width > width && !starts_block(word) {
+                result += &current;
+                result.push('\n');
+                current = continuation.clone();
+                current_width = continuation.chars().count();
+                is_empty = true;
+            }
+
+            if !is_empty {
+                current.push(' ');
+                current_width += 1;
+            }
+
+            current += word;
+            current_width += word_width;
+            is_empty = false;
+        }
+
+        result += &current;
+    }
+
+    result
+}
+
+/// Whether a word starting a line would start a Markdown block, like `-` or `1.` a list item, or
+/// `>` a quote.
+fn starts_block(word: &str) -> bool {
+    let digits = word.len() - word.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+
+    matches!(word, "-" | "*" | "+")
+        || word.starts_with('>')
+        || (digits > 0 && matches!(&word[digits..], "." | ")"))
+}
+
+/// Converts the items of Doxygen's ordered lists, like `-# item`, to numbered Markdown items. The
+/// items indented further than the previous item are nested under it.
+///
+/// Code blocks are kept as-is.
+pub(crate) fn ordered_lists(input: &str) -> String {
+    let mut result = String::with_capacity(input.len());
+    let mut in_code_block = false;
+    // The indentation and the number of the items of the enclosing lists
+    let mut lists: Vec<(usize, usize)> = vec![];
+
+    for (index, line) in input.split('\n').enumerate() {
+        if index > 0 {
+            result.push('\n');
+        }
+
+        let content = line.trim_start_matches(' ');
+        let indent = line.len() - content.len();
+        if content.starts_with("```") {
+            in_code_block = !in_code_block;
+        }
+
+        let item = content
+            .strip_prefix("-#")
+            .filter(|rest| rest.is_empty() || rest.starts_with(' '));
+
+        match item {
+            Some(rest) if !in_code_block => {
+                while lists
+                    .last()
+                    .is_some_and(|(list_indent, _)| *list_indent > indent)
+                {
+                    lists.pop();
+                }
+
+                match lists.last_mut() {
+                    Some((list_indent, number)) if *list_indent == indent => *number += 1,
+                    _ => lists.push((indent, 1)),
+                }
+
+                // The items are nested by indenting them to the content of the enclosing item
+                let nesting = lists[..lists.len() - 1]
+                    .iter()
+                    .map(|(_, number)| number.to_string().len() + 2)
+                    .sum::<usize>();
+                let number = lists.last().map_or(1, |(_, number)| *number);
+                result += &format!("{}{number}.{rest}", " ".repeat(nesting));
+            }
+            _ => {
+                // The lists end at blank lines and at the lines less indented than their items
+                if content.is_empty() || lists.first().is_some_and(|(first, _)| indent <= *first) {
+                    lists.clear();
+                }
+
+                result += line;
+            }
+        }
+    }
+
+    result
+}
+
+/// Indents the lines continuing the list items (up to the next item or blank line) by `indent`
+/// spaces, replacing their indentation.
+///
+/// Nested lists, block quotes and code blocks are kept as-is.
+pub(crate) fn indent_list_items(input: &str, indent: usize) -> String {
+    let mut result = String::with_capacity(input.len());
+    let mut in_code_block = false;
+    let mut in_item = false;
+
+    for (index, line) in input.split('\n').enumerate() {
+        if index > 0 {
+            result.push('\n');
+        }
+
+        let content = line.trim_start_matches(' ');
+        if content.starts_with("```") {
+            in_code_block = !in_code_block;
+        }
+
+        if in_code_block || content.is_empty() || content.starts_with(['#', '>']) {
+            in_item = false;
+        } else if line.len() == content.len() && list_marker_len(content) > 0 {
+            in_item = true;
+        } else if in_item && list_marker_len(content) == 0 {
+            result += &" ".repeat(indent);
+            result += content;
+            continue;
+        }
+
+        result += line;
+    }
+
+    result
+}
+
+/// Escapes the `#` starting the lines of text (in block quotes too) when it's followed by a word,
+/// like in `#include <x.h>` or `#define FOO`, which some renderers take for headings. The Markdown
+/// headings, like `# Usage`, are kept.
+///
+/// Code blocks are kept as-is.
+pub(crate) fn escape_hashes(input: &str) -> String {
+    let mut result = String::with_capacity(input.len());
+    let mut in_code_block = false;
+
+    for (index, line) in input.split('\n').enumerate() {
+        if index > 0 {
+            result.push('\n');
+        }
+
+        let mut content = line.trim_start_matches(' ');
+        if content.starts_with("```") {
+            in_code_block = !in_code_block;
+        }
+
+        while let Some(quoted) = content.strip_prefix('>') {
+            content = quoted.trim_start_matches(' ');
+        }
+
+        let is_word = content
+            .strip_prefix('#')
+            .is_some_and(|rest| rest.starts_with(char::is_alphanumeric));
+        if in_code_block || !is_word {
+            result += line;
+            continue;
+        }
+
+        let start = line.len() - content.len();
+        result += &line[..start];
+        result.push('\\');
+        result += content;
+    }
+
+    result
+}
+
+/// Adds the prefix to every line, like `/// ` to embed the Rustdoc in source code. The prefix
+/// added to the blank lines is trimmed, so they don't get trailing whitespace.
+pub(crate) fn prefix_lines(input: &str, prefix: &str) -> String {
+    let mut result = String::with_capacity(input.len() + prefix.len() * 8);
+
+    for (index, line) in input.split('\n').enumerate() {
+        if index > 0 {
+            result.push('\n');
+        }
+
+        match line.is_empty() {
+            true => result += prefix.trim_end(),
+            false => {
+                result += prefix;
+                result += line;
+            }
+        }
+    }
+
+    result
+}
+
+/// Returns the length of the list marker at the start of the line, like `* ` or `1. `, or 0 if
+/// there's none.
+fn list_marker_len(line: &str) -> usize {
+    if line.starts_with("* ") || line.starts_with("- ") {
+        2
+    } else {
+        let digits = line.chars().take_while(char::is_ascii_digit).count();
+        if digits > 0 && line[digits..].starts_with(". ") {
+            digits + 2
+        } else {
+            0
+        }
+    }
+}
+
+/// Converts the HTML links, like `<a href="https://example.com">Example</a>`, to Markdown links.
+///
+/// Code blocks and the links that don't fit on a single line are kept as-is.
+pub(crate) fn markdown_links(input: &str) -> String {
+    let mut result = String::with_capacity(input.len());
+    let mut in_code_block = false;
+
+    for (index, line) in input.split('\n').enumerate() {
+        if index > 0 {
+            result.push('\n');
+        }
+
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+        }
+
+        if in_code_block {
+            result += line;
+            continue;
+        }
+
+        let mut rest = line;
+        while let Some(start) = rest.find("<a ") {
+            result += &rest[..start];
+            rest = &rest[start..];
+
+            match split_html_link(rest) {
+                Some((url, text, length)) => {
+                    result += &format!("[{text}]({url})");
+                    rest = &rest[length..];
+                }
+                None => {
+                    result += "<a ";
+                    rest = &rest[3..];
+                }
+            }
+        }
+
+        result += rest;
+    }
+
+    result
+}
+
+/// Splits the URL and the text of the HTML link at the start of the input, returning them with
+/// the length of the link.
+fn split_html_link(input: &str) -> Option<(&str, &str, usize)> {
+    let rest = input
+        .strip_prefix("<a")?
+        .trim_start()
+        .strip_prefix("href=")?;
+    let quote = rest.chars().next().filter(|c| matches!(c, '"' | '\''))?;
+    let rest = &rest[1..];
+
+    let url_end = rest.find(quote)?;
+    let url = &rest[..url_end];
+    let rest = rest[url_end + 1..].trim_start().strip_prefix('>')?;
+
+    let text_end = rest.find("</a>")?;
+    let text = rest[..text_end].trim();
+    if url.is_empty() || text.is_empty() {
+        return None;
+    }
+
+    let length = input.len() - rest.len() + text_end + "</a>".len();
+    Some((url, text, length))
+}
+
+/// Splits a line into its prefix (indentation, block quote markers and list markers), the prefix
+/// to use on wrapped lines, and the content. The wrapped lines of list items are indented by
+/// `list_indent` spaces (if given) instead of the width of the marker.
+fn split_prefix(line: &str, list_indent: Option<usize>) -> (&str, String, &str) {
+    let mut rest = line.trim_start_matches(' ');
+    let mut continuation = " ".repeat(line.len() - rest.len());
+
+    while let Some(stripped) = rest.strip_prefix("> ") {
+        continuation += "> ";
+        rest = stripped;
+    }
+
+    let marker_len = list_marker_len(rest);
+    continuation += &" ".repeat(match list_indent {
+        Some(indent) if marker_len > 0 => indent,
+        _ => marker_len,
+    });
+
+    let prefix_len = line.len() - rest.len() + marker_len;
+    (&line[..prefix_len], continuation, &line[prefix_len..])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn normalizes_whitespace() {
+        assert_eq!(
+            normalize_whitespace("\n# Arguments \n\n\n\n\n* `a` - A \n"),
+            "# Arguments\n\n\n* `a` - A"
+        );
+        assert_eq!(normalize_whitespace(" \n\n  - a \n  - b"), "  - a\n  - b");
+        assert_eq!(
+            normalize_whitespace("```\nlet a = 1;  \n\n\n\n```"),
+            "```\nlet a = 1;  \n\n\n\n```"
+        );
+    }
+
+    #[test]
+    fn converts_html_links() {
+        assert_eq!(
+            markdown_links("See <a href=\"https://example.com\">the example</a>."),
+            "See [the example](https://example.com)."
+        );
+        assert_eq!(
+            markdown_links("<a name=\"x\">Anchor</a>\n```\n<a href='x'>y</a>\n```"),
+            "<a name=\"x\">Anchor</a>\n```\n<a href='x'>y</a>\n```"
+        );
+    }
+
+    #[test]
+    fn wraps_prose() {
+        assert_eq!(
+            wrap("This is a long line\n# This is a long heading", 10, None),
+            "This is a\nlong line\n# This is a long heading"
+        );
+        assert_eq!(
+            wrap("The values are aaaa - bbbb", 20, None),
+            "The values are aaaa -\nbbbb"
+        );
+        assert_eq!(
+            wrap("Counts up to 1. Then > 2 or + 3", 13, None),
+            "Counts up to 1.\nThen > 2 or +\n3"
+        );
+    }
+
+    #[test]
+    fn wraps_with_prefix() {
+        assert_eq!(
+            wrap("* `example` - This is an example.", 20, None),
+            "* `example` - This\n  is an example."
+        );
+        assert_eq!(
+            wrap("> **Note:** This is a note.", 16, None),
+            "> **Note:** This\n> is a note."
+        );
+    }
+
+    #[test]
+    fn indents_list_items() {
+        let input = "* `a` - A\nvalue\n * `b` - B\n\nText\n  indented";
+        assert_eq!(
+            indent_list_items(input, 4),
+            "* `a` - A\n    value\n * `b` - B\n\nText\n  indented"
+        );
+        assert_eq!(
+            wrap("- `example` - This is an example.", 20, Some(4)),
+            "- `example` - This\n    is an example."
+        );
+    }
+
+    #[test]
+    fn escapes_hashes() {
+        assert_eq!(
+            escape_hashes(
+                "#include <x.h>\n# Usage\n> #define FOO\n  #1 and #2\n```\n#define X\n```"
+            ),
+            "\\#include <x.h>\n# Usage\n> \\#define FOO\n  \\#1 and #2\n```\n#define X\n```"
+        );
+    }
+
+    #[test]
+    fn prefixes_lines() {
+        assert_eq!(
+            prefix_lines("# Arguments\n\n* `a` - A", "/// "),
+            "/// # Arguments\n///\n/// * `a` - A"
+        );
+        assert_eq!(
+            prefix_lines("```\nx\n```", "    "),
+            "    ```\n    x\n    ```"
+        );
+    }
+
+    #[test]
+    fn converts_ordered_lists() {
+        assert_eq!(
+            ordered_lists("Steps:\n-# First\n  -# Nested\n  -# Other\n-# Second\n\n-# New"),
+            "Steps:\n1. First\n   1. Nested\n   2. Other\n2. Second\n\n1. New"
+        );
+        assert_eq!(ordered_lists("```\n-# x\n```"), "```\n-# x\n```");
+    }
+}
@@ -1,5 +1,19 @@
+use std::collections::HashMap;
+use std::ops::Range;
+use std::{fmt, io};
+
+#[cfg(feature = "emoji")]
 use crate::emojis;
-use crate::parser::{parse, GrammarItem, ParseError};
+use crate::format;
+use crate::lexer::{is_url, split_punctuation};
+use crate::options::{AdmonitionStyle, DirectionStyle, HeadingStyle, Options, Section};
+use crate::parser::{autolink, parse_recovering, GrammarItem};
+use crate::source_map::SourceMap;
+use crate::stats::Stats;
+use crate::tag::Tag;
+use crate::tagfile;
+
+pub use crate::parser::ParseError;
 
 /// Creates a Rustdoc string from a Doxygen string.
 ///
@@ -8,180 +22,1510 @@ use crate::parser::{parse, GrammarItem, ParseError};
 /// This function can error if there are missing parts of a given Doxygen annotation (like `@param`
 /// missing the variable name)
 pub fn rustdoc(input: String) -> Result<String, ParseError> {
-    let parsed = parse(input)?;
-    let mut result = String::new();
-    let mut already_added_params = false;
-    let mut already_added_returns = false;
-    let mut already_added_throws = false;
-    let mut group_started = false;
-
-    for item in parsed {
-        result += &match item {
-            GrammarItem::Notation { meta, params, tag } => {
-                let (str, (added_param, added_return, added_throws)) = generate_notation(
-                    tag,
-                    meta,
-                    params,
-                    (
-                        already_added_params,
-                        already_added_returns,
-                        already_added_throws,
-                    ),
-                );
-                if added_param {
-                    already_added_params = true;
-                }
+    rustdoc_with_options(input, &Options::default())
+}
 
-                if added_return {
-                    already_added_returns = true;
-                }
+/// Creates a Rustdoc string from a Doxygen string, using the given [`Options`].
+///
+/// # Errors
+///
+/// Besides the errors returned by [`rustdoc`], this function errors with
+/// [`ParseError::UnknownTag`] if [`Options::strict`] is enabled and a tag can't be translated.
+pub fn rustdoc_with_options(input: String, options: &Options) -> Result<String, ParseError> {
+    rustdoc_with_stats(input, options, &mut Stats::default())
+}
 
-                if added_throws {
-                    already_added_throws = true;
-                }
+/// Creates a Rustdoc string from a Doxygen string, recording the tags used into `stats`.
+///
+/// # Errors
+///
+/// See [`rustdoc_with_options`].
+pub fn rustdoc_with_stats(
+    input: String,
+    options: &Options,
+    stats: &mut Stats,
+) -> Result<String, ParseError> {
+    // The Rustdoc is usually about as long as the Doxygen
+    let mut rustdoc = String::with_capacity(input.len());
+    generate(&input, options, stats, None, &mut rustdoc)?;
 
-                str
+    Ok(rustdoc)
+}
+
+/// Creates a Rustdoc string from a Doxygen string, along with the [`SourceMap`] of its lines to
+/// the parts of the input they were generated from.
+///
+/// # Errors
+///
+/// See [`rustdoc_with_options`].
+pub fn rustdoc_with_source_map(
+    input: String,
+    options: &Options,
+) -> Result<(String, SourceMap), ParseError> {
+    let rustdoc = rustdoc_with_options(input.clone(), options)?;
+    let source_map = SourceMap::new(&input, &rustdoc);
+
+    Ok((rustdoc, source_map))
+}
+
+/// Creates a Rustdoc string from Doxygen bytes that may not be valid UTF-8, like the comments of
+/// headers encoded in Latin-1 or Shift-JIS. The invalid sequences are replaced with `�`.
+///
+/// # Examples
+///
+/// ```
+/// use doxygen_rs::generator::rustdoc_from_bytes;
+/// use doxygen_rs::options::Options;
+///
+/// let rustdoc = rustdoc_from_bytes(b"@brief Caf\xe9", &Options::default()).unwrap();
+/// assert_eq!(rustdoc, "Caf\u{FFFD}");
+/// ```
+///
+/// # Errors
+///
+/// See [`rustdoc_with_options`].
+pub fn rustdoc_from_bytes(input: &[u8], options: &Options) -> Result<String, ParseError> {
+    rustdoc_with_options(String::from_utf8_lossy(input).into_owned(), options)
+}
+
+/// Writes the Rustdoc created from a Doxygen string into `writer`, like a buffer reused between
+/// the comments of a batch conversion.
+///
+/// # Examples
+///
+/// ```
+/// use doxygen_rs::generator::rustdoc_to;
+///
+/// let mut rustdoc = String::new();
+/// for comment in ["@brief First", "@brief Second"] {
+///     rustdoc_to(&mut rustdoc, comment.into()).unwrap();
+///     rustdoc.push('\n');
+/// }
+///
+/// assert_eq!(rustdoc, "First\nSecond\n");
+/// ```
+///
+/// # Errors
+///
+/// See [`rustdoc`]. This function also errors with [`ParseError::WriteFailed`] if `writer` fails.
+pub fn rustdoc_to(writer: &mut impl fmt::Write, input: String) -> Result<(), ParseError> {
+    rustdoc_to_with_options(writer, input, &Options::default())
+}
+
+/// Writes the Rustdoc created from a Doxygen string into `writer`, using the given [`Options`].
+///
+/// # Errors
+///
+/// See [`rustdoc_with_options`] and [`rustdoc_to`].
+pub fn rustdoc_to_with_options(
+    writer: &mut impl fmt::Write,
+    input: String,
+    options: &Options,
+) -> Result<(), ParseError> {
+    generate(&input, options, &mut Stats::default(), None, writer)
+}
+
+/// Writes the Rustdoc created from a Doxygen string into an [`io::Write`], like a file or the
+/// standard output. The sections are written as they're generated, so `writer` is usually
+/// buffered (like with [`io::BufWriter`]).
+///
+/// # Examples
+///
+/// ```
+/// use doxygen_rs::generator::rustdoc_to_io;
+///
+/// let mut rustdoc = vec![];
+/// rustdoc_to_io(&mut rustdoc, "@brief Opens it".into()).unwrap();
+///
+/// assert_eq!(rustdoc, b"Opens it");
+/// ```
+///
+/// # Errors
+///
+/// See [`rustdoc_to`].
+pub fn rustdoc_to_io(writer: &mut impl io::Write, input: String) -> Result<(), ParseError> {
+    rustdoc_to_io_with_options(writer, input, &Options::default())
+}
+
+/// Writes the Rustdoc created from a Doxygen string into an [`io::Write`], using the given
+/// [`Options`].
+///
+/// # Errors
+///
+/// See [`rustdoc_to_with_options`].
+pub fn rustdoc_to_io_with_options(
+    writer: &mut impl io::Write,
+    input: String,
+    options: &Options,
+) -> Result<(), ParseError> {
+    rustdoc_to_with_options(&mut IoWriter(writer), input, options)
+}
+
+/// Adapts an [`io::Write`] to a [`fmt::Write`].
+struct IoWriter<W>(W);
+
+impl<W: io::Write> fmt::Write for IoWriter<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0.write_all(s.as_bytes()).map_err(|_| fmt::Error)
+    }
+}
+
+/// Creates a Rustdoc string from a Doxygen string, without ever failing or panicking.
+///
+/// Malformed annotations (like `@c` missing the word to style) are kept as their literal text, and
+/// the errors that would've been returned by [`rustdoc_with_options`] are returned as warnings
+/// instead. Unknown emojis are reported as warnings too, as they're likely typos.
+///
+/// If the input exceeds the [`Options::limits`], the Rustdoc is empty and the warnings contain the
+/// [`ParseError::LimitExceeded`].
+///
+/// # Examples
+///
+/// ```
+/// use doxygen_rs::generator::rustdoc_lossy;
+/// use doxygen_rs::options::Options;
+///
+/// let (rustdoc, warnings) = rustdoc_lossy("Styled @c".into(), &Options::default());
+/// assert_eq!(rustdoc, "Styled @c");
+/// assert_eq!(warnings.len(), 1);
+/// ```
+pub fn rustdoc_lossy(input: String, options: &Options) -> (String, Vec<ParseError>) {
+    rustdoc_lossy_with_stats(input, options, &mut Stats::default())
+}
+
+/// Translates many comments with the same [`Options`], reusing its buffers between them instead of
+/// allocating them for every comment, like for the thousands of comments of a large binding.
+///
+/// # Examples
+///
+/// ```
+/// use doxygen_rs::generator::Converter;
+/// use doxygen_rs::options::Options;
+///
+/// let mut converter = Converter::new(Options::default());
+///
+/// assert_eq!(converter.convert("@brief First").unwrap(), "First");
+/// assert_eq!(converter.convert("@brief Second").unwrap(), "Second");
+/// assert_eq!(converter.stats().comments, 2);
+/// ```
+#[derive(Debug)]
+pub struct Converter {
+    options: Options,
+    stats: Stats,
+    output: String,
+}
+
+impl Converter {
+    /// Creates a converter translating with the given options.
+    pub fn new(options: Options) -> Self {
+        Self {
+            options,
+            stats: Stats::default(),
+            output: String::new(),
+        }
+    }
+
+    /// Returns the options of the converter.
+    pub fn options(&self) -> &Options {
+        &self.options
+    }
+
+    /// Returns the tags used by all the comments converted so far.
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    /// Creates a Rustdoc string from a Doxygen string. The returned string is overwritten by the
+    /// next conversion.
+    ///
+    /// # Errors
+    ///
+    /// See [`rustdoc_with_options`].
+    pub fn convert(&mut self, input: &str) -> Result<&str, ParseError> {
+        self.output.clear();
+        generate(
+            input,
+            &self.options,
+            &mut self.stats,
+            None,
+            &mut self.output,
+        )?;
+
+        Ok(&self.output)
+    }
+}
+
+/// How serious a [`Diagnostic`] is.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Severity {
+    /// The annotation is malformed and was kept as its literal text. [`rustdoc_with_options`]
+    /// would've failed with this error.
+    Error,
+    /// The comment was translated, but is likely wrong, like a `@param` without description.
+    Warning,
+}
+
+/// An error or warning found by [`rustdoc_partial`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub error: ParseError,
+}
+
+/// Creates a Rustdoc string from a Doxygen string, translating everything but the malformed
+/// annotations.
+///
+/// Like [`rustdoc_lossy`], translation resumes right after a malformed annotation (which is kept as
+/// its literal text), so the rest of the comment is still translated. The diagnostics tell apart
+/// the errors [`rustdoc_with_options`] would've failed with from the warnings.
+///
+/// # Examples
+///
+/// ```
+/// use doxygen_rs::generator::{rustdoc_partial, Severity};
+/// use doxygen_rs::options::Options;
+///
+/// let input = "@brief Example\n@param a\n@return The value of @c";
+/// let (rustdoc, diagnostics) = rustdoc_partial(input.into(), &Options::default());
+///
+/// assert_eq!(rustdoc, "Example\n\n# Arguments\n\n* `a`\n\n# Returns\n\nThe value of @c");
+/// assert_eq!(diagnostics[0].severity, Severity::Warning);
+/// assert_eq!(diagnostics[1].severity, Severity::Error);
+/// ```
+pub fn rustdoc_partial(input: String, options: &Options) -> (String, Vec<Diagnostic>) {
+    let (rustdoc, warnings) = rustdoc_lossy(input, options);
+    let diagnostics = warnings
+        .into_iter()
+        .map(|error| Diagnostic {
+            severity: severity(&error, options),
+            error,
+        })
+        .collect();
+
+    (rustdoc, diagnostics)
+}
+
+/// The translation of a comment by [`rustdoc_batch`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BatchResult<K> {
+    /// The ID of the comment, as given to [`rustdoc_batch`].
+    pub id: K,
+    /// The Rustdoc, or the first error [`rustdoc_with_options`] would've failed with.
+    pub rustdoc: Result<String, ParseError>,
+    /// The errors and warnings found in the comment, see [`rustdoc_partial`].
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Translates many comments independently, identifying each of them with an ID (like its index
+/// or the name of the item it documents), so a malformed comment doesn't prevent the translation
+/// of the others. The buffers are reused between the comments, like with [`Converter`].
+///
+/// # Examples
+///
+/// ```
+/// use doxygen_rs::generator::rustdoc_batch;
+/// use doxygen_rs::options::Options;
+///
+/// let comments = [("open", "@brief Opens a file."), ("close", "@brief Closes @c")];
+/// let results = rustdoc_batch(comments, &Options::default());
+///
+/// assert_eq!(results[0].id, "open");
+/// assert_eq!(results[0].rustdoc.as_deref(), Ok("Opens a file."));
+/// assert_eq!(results[1].id, "close");
+/// assert!(results[1].rustdoc.is_err());
+/// ```
+pub fn rustdoc_batch<K, S: AsRef<str>>(
+    inputs: impl IntoIterator<Item = (K, S)>,
+    options: &Options,
+) -> Vec<BatchResult<K>> {
+    let mut stats = Stats::default();
+
+    inputs
+        .into_iter()
+        .map(|(id, input)| {
+            let input = input.as_ref();
+            let mut warnings = vec![];
+            let mut rustdoc = String::with_capacity(input.len());
+
+            let result = generate(
+                input,
+                options,
+                &mut stats,
+                Some(&mut warnings),
+                &mut rustdoc,
+            );
+            if let Err(error) = result {
+                warnings.push(error);
             }
-            GrammarItem::Text(v) => if group_started {
-                v.replacen("*", "", 1)
-            } else {
-                v
-            },
-            // See <https://stackoverflow.com/a/40354789>
-            GrammarItem::GroupStart => {
-                group_started = true;
-                String::from("# ")
-            },
-            GrammarItem::GroupEnd => {
-                group_started = false;
-                continue
-            },
-        };
+
+            let diagnostics = warnings
+                .into_iter()
+                .map(|error| Diagnostic {
+                    severity: severity(&error, options),
+                    error,
+                })
+                .collect::<Vec<_>>();
+            let rustdoc = match diagnostics
+                .iter()
+                .find(|diagnostic| diagnostic.severity == Severity::Error)
+            {
+                Some(diagnostic) => Err(diagnostic.error.clone()),
+                None => Ok(rustdoc),
+            };
+
+            BatchResult {
+                id,
+                rustdoc,
+                diagnostics,
+            }
+        })
+        .collect()
+}
+
+/// Returns whether the error makes [`rustdoc_with_options`] fail.
+fn severity(error: &ParseError, options: &Options) -> Severity {
+    match error {
+        ParseError::UnknownEmoji { .. } if !options.strict => Severity::Warning,
+        ParseError::MissingParamName { .. }
+        | ParseError::MissingDescription { .. }
+        | ParseError::IgnoredTag { .. }
+        | ParseError::UnmatchedTag { .. } => Severity::Warning,
+        _ => Severity::Error,
+    }
+}
+
+/// Like [`rustdoc_lossy`], recording the tags used into `stats`.
+pub(crate) fn rustdoc_lossy_with_stats(
+    input: String,
+    options: &Options,
+    stats: &mut Stats,
+) -> (String, Vec<ParseError>) {
+    let mut warnings = vec![];
+    let mut rustdoc = String::with_capacity(input.len());
+
+    if let Err(error) = generate(&input, options, stats, Some(&mut warnings), &mut rustdoc) {
+        debug!(%error, "failed to translate the comment");
+        rustdoc.clear();
+        warnings.push(error);
     }
 
-    Ok(result)
+    (rustdoc, warnings)
 }
 
-fn generate_notation(
-    tag: String,
-    meta: Vec<String>,
-    params: Vec<String>,
-    (already_params, already_returns, already_throws): (bool, bool, bool),
-) -> (String, (bool, bool, bool)) {
-    let mut new_param = false;
-    let mut new_return = false;
-    let mut new_throw = false;
+/// Generates the Rustdoc into `writer`, recovering from the errors by pushing them into `warnings`
+/// (if any).
+fn generate(
+    input: &str,
+    options: &Options,
+    stats: &mut Stats,
+    mut warnings: Option<&mut Vec<ParseError>>,
+    writer: &mut impl fmt::Write,
+) -> Result<(), ParseError> {
+    let parsed = parse_recovering(input, options, warnings.as_deref_mut())?;
+    generate_parsed(parsed, input.len(), options, stats, warnings, writer)
+}
+
+/// Like [`generate`], from the already parsed items of an input `len` bytes long.
+pub(crate) fn generate_parsed(
+    parsed: Vec<GrammarItem>,
+    len: usize,
+    options: &Options,
+    stats: &mut Stats,
+    mut warnings: Option<&mut Vec<ParseError>>,
+    writer: &mut impl fmt::Write,
+) -> Result<(), ParseError> {
+    let mut output = Output::with_capacity(len);
+    let mut generator = Generator::new(options, stats);
+    let mut group_depth = 0;
+    // Whether the space starting the next text is left out, after a tag without any output
+    let mut trim_space = false;
+    // Whether a code block (or verbatim block) is open, which an `@endcode` ends
+    let mut in_code_block = false;
+
+    let mut items = parsed.into_iter().peekable();
+    while let Some(item) = items.next() {
+        let str = match item {
+            GrammarItem::Notation {
+                meta,
+                params,
+                tag,
+                line,
+                span,
+            } => {
+                *generator.stats.tags.entry(tag.to_string()).or_default() += 1;
+
+                // An end without its start would open a code block instead, so it's dropped
+                let is_end = matches!(tag, Tag::Endcode | Tag::Endverbatim);
+                let unmatched = is_end && !in_code_block;
+                in_code_block =
+                    matches!(tag, Tag::Code | Tag::Verbatim) || (in_code_block && !is_end);
+                if unmatched {
+                    if let Some(warnings) = warnings.as_deref_mut() {
+                        warnings.push(ParseError::UnmatchedTag {
+                            tag: tag.to_string(),
+                            line,
+                            span: span.clone(),
+                        });
+                    }
+                }
+
+                if tag.is_block_command() && !unmatched {
+                    output.set_target(Target::Body);
+                }
+
+                if tag == Tag::Code && options.gather_examples {
+                    output.set_target(Target::Examples);
+                }
+
+                let generated = if unmatched {
+                    Ok(String::new())
+                } else if tag == Tag::Par && is_untitled(items.peek()) {
+                    // The text after an untitled `@par` starts with a new line, which starts the
+                    // new paragraph
+                    let str = if output.body.is_empty() || output.body.ends_with('\n') {
+                        String::new()
+                    } else {
+                        String::from("\n")
+                    };
 
-    (
-        match tag.as_str() {
-            "param" => {
-                let param = params.get(0);
-                new_param = true;
-                let mut str = if !already_params {
-                    "# Arguments\n\n".into()
+                    Ok(str)
                 } else {
-                    String::new()
+                    generator.notation(
+                        &tag,
+                        &meta,
+                        &params,
+                        line,
+                        &span,
+                        has_description(items.peek()),
+                    )
                 };
+                debug!(%tag, ?meta, ?params, line, output = ?generated, "translated a tag");
 
-                str += &if let Some(param) = param {
-                    if meta.is_empty() {
-                        format!("* `{param}` -")
-                    } else {
-                        if let Some(second) = meta.get(1) {
-                            format!(
-                                "* `{}` (direction {}, {}) -",
-                                param,
-                                meta.get(0).unwrap(),
-                                second
-                            )
-                        } else {
-                            format!("* `{}` (direction {}) -", param, meta.get(0).unwrap())
+                if tag == Tag::Param && !params.is_empty() && !has_description(items.peek()) {
+                    if let Some(warnings) = warnings.as_deref_mut() {
+                        warnings.push(ParseError::MissingDescription {
+                            tag: tag.to_string(),
+                            name: params[0].clone(),
+                            line,
+                            span: span.clone(),
+                        });
+                    }
+                }
+
+                if tag == Tag::Param && params.is_empty() {
+                    if let Some(warnings) = warnings.as_deref_mut() {
+                        warnings.push(ParseError::MissingParamName {
+                            tag: tag.to_string(),
+                            line,
+                            span: span.clone(),
+                        });
+                    }
+                }
+
+                if tag == Tag::Addindex
+                    || tag.visibility().is_some()
+                    || (tag.is_display_control() && options.strict)
+                {
+                    if let Some(warnings) = warnings.as_deref_mut() {
+                        warnings.push(ParseError::IgnoredTag {
+                            tag: tag.to_string(),
+                            line,
+                            span: span.clone(),
+                        });
+                    }
+                }
+
+                // Unknown emojis are only an error in strict mode, but likely typos
+                if tag == Tag::Emoji
+                    && options.expand_emojis
+                    && !options.strict
+                    && cfg!(feature = "emoji")
+                {
+                    if let (Some(warnings), Some(emoji)) = (warnings.as_deref_mut(), params.first())
+                    {
+                        if find_emoji(&emoji.replace(':', ""), options).is_none() {
+                            warnings.push(ParseError::UnknownEmoji {
+                                emoji: emoji.clone(),
+                                line,
+                                span: span.clone(),
+                            });
                         }
                     }
+                }
+
+                let str = match generated {
+                    Ok(str) => str,
+                    Err(error) => {
+                        let Some(warnings) = warnings.as_deref_mut() else {
+                            return Err(error);
+                        };
+
+                        debug!(%error, "kept the tag as it is");
+                        warnings.push(error);
+                        literal_notation(&tag, &meta, &params)
+                    }
+                };
+
+                // The fences of a code block must be on their own lines
+                let str = if matches!(
+                    tag,
+                    Tag::Code | Tag::Endcode | Tag::Verbatim | Tag::Endverbatim
+                ) && !unmatched
+                {
+                    output.trim_end();
+                    let current = output.current();
+
+                    if current.is_empty() || current.ends_with('\n') {
+                        str
+                    } else {
+                        format!("\n{str}")
+                    }
                 } else {
-                    String::new()
+                    str
                 };
 
-                str
-            }
-            "a" | "e" | "em" => {
-                let word = params
-                    .get(0)
-                    .expect("@a/@e/@em doesn't contain a word to style");
-                format!("_{word}_")
-            }
-            "b" => {
-                let word = params.get(0).expect("@b doesn't contain a word to style");
-                format!("**{word}**")
-            }
-            "c" | "p" => {
-                let word = params
-                    .get(0)
-                    .expect("@c/@p doesn't contain a word to style");
-                format!("`{word}`")
-            }
-            "emoji" => {
-                let word = params.get(0).expect("@emoji doesn't contain an emoji");
-                emojis::EMOJIS
-                    .get(&word.replace(':', ""))
-                    .expect("invalid emoji")
-                    .to_string()
-            }
-            "sa" | "see" => {
-                let code_ref = params.get(0).expect("@sa/@see doesn't contain a reference");
-                format!("[`{code_ref}`]")
-            }
-            "retval" => {
-                let var = params.get(0).expect("@retval doesn't contain a parameter");
-                new_return = true;
-                let mut str = if !already_returns {
-                    "# Returns\n\n".into()
+                // The diagrams are paragraphs of their own
+                let str = if matches!(tag, Tag::Dotfile | Tag::Mscfile | Tag::Diafile) {
+                    output.trim_end();
+                    let current = output.current();
+
+                    let separator = if current.is_empty() || current.ends_with("\n\n") {
+                        ""
+                    } else if current.ends_with('\n') {
+                        "\n"
+                    } else {
+                        "\n\n"
+                    };
+                    format!("{separator}{str}")
                 } else {
-                    String::new()
+                    str
                 };
 
-                str += &format!("* `{var}` -");
+                let str = match options.provenance {
+                    true => provenance(&tag, line, str),
+                    false => str,
+                };
+
+                // Only one of the spaces around an invisible tag is kept, like in `a @qualifier x b`,
+                // and none of the line it's alone on. The space before the tags without parameters
+                // (like `@hiderefs`) is the one kept, as the one after them is already left out.
+                if (tag == Tag::Qualifier
+                    || tag.is_structural()
+                    || tag.is_foreign_region()
+                    || tag.is_display_control()
+                    || unmatched)
+                    && str.is_empty()
+                {
+                    let current = output.current();
+                    if current.is_empty() || current.ends_with('\n') {
+                        trim_space = true;
+                    } else if tag == Tag::Qualifier || tag.is_structural() {
+                        output.trim_end();
+                    }
+                }
+
+                let returns = &mut output.returns;
+                match tag {
+                    Tag::Brief | Tag::Short => output.set_target(Target::Brief),
+                    Tag::Note
+                    | Tag::Important
+                    | Tag::Warning
+                    | Tag::Attention
+                    | Tag::Remark
+                    | Tag::Remarks
+                    | Tag::Since
+                    | Tag::Deprecated => output.start_quote(&tag, &str, &options.admonition_style),
+                    Tag::Retval if !params.is_empty() => {
+                        returns.found = true;
+                        returns
+                            .retvals_position
+                            .get_or_insert(returns.description.len());
+                        returns.retvals.push((params[0].clone(), String::new()));
+                        let index = returns.retvals.len() - 1;
+                        output.set_target(Target::Retval(index));
+                    }
+                    Tag::Returns | Tag::Return | Tag::Result => {
+                        returns.found = true;
+                        output.set_target(Target::Returns);
+                    }
+                    Tag::Param => output.start_entry(Target::Arguments),
+                    Tag::Throw | Tag::Throws | Tag::Exception => output.start_entry(Target::Errors),
+                    Tag::Pre if options.pre_as_safety => output.start_entry(Target::Safety),
+                    Tag::Sa | Tag::See => output.start_entry(Target::SeeAlso),
+                    Tag::Endcode if output.target == Target::Examples && !unmatched => {
+                        output.push(&str);
+                        output.set_target(Target::Body);
+                        continue;
+                    }
+                    _ => {}
+                }
+
                 str
             }
-            "returns" | "return" | "result" => {
-                new_return = true;
-                if !already_returns {
-                    "# Returns\n\n".into()
+            GrammarItem::Text(v) => {
+                let v = if group_depth > 0 {
+                    strip_decoration(&v)
                 } else {
-                    String::new()
+                    v
+                };
+                let v = match std::mem::take(&mut trim_space) {
+                    true => {
+                        let rest = v.strip_prefix(' ').unwrap_or(&v);
+                        rest.strip_prefix('\n').unwrap_or(rest).to_string()
+                    }
+                    false => v,
+                };
+
+                trace!(text = ?v, target = ?output.target, "generated text");
+
+                let end = match output.target {
+                    Target::Body => None,
+                    // Like the others, the brief extends up to the next blank line
+                    _ => paragraph_end(&v),
+                };
+
+                match end {
+                    Some(end) => {
+                        output.push(&v[..end]);
+                        output.set_target(Target::Body);
+                        v[end..].to_string()
+                    }
+                    None => v,
                 }
             }
-            "throw" | "throws" | "exception" => {
-                new_throw = true;
-                let exception = params.get(0).expect("@param doesn't contain a parameter");
+            GrammarItem::LinkTarget { target, .. } => {
+                *generator
+                    .stats
+                    .tags
+                    .entry(Tag::Ref.to_string())
+                    .or_default() += 1;
+                trim_space = false;
 
-                let mut str = if !already_throws {
-                    "# Throws\n\n".into()
-                } else {
-                    String::new()
-                };
+                let url = link_target(&target, options);
+                debug!(target, output = ?url, "translated a link target");
+                url
+            }
+            // See <https://stackoverflow.com/a/40354789>
+            GrammarItem::GroupStart => {
+                group_depth += 1;
+                heading_prefix(options, group_depth - 1)
+            }
+            GrammarItem::GroupEnd => {
+                group_depth = usize::saturating_sub(group_depth, 1);
+                continue;
+            }
+        };
 
-                str += &format!("* [`{exception}`] -");
-                str
+        output.push(&str);
+    }
+
+    // The sections are written as soon as they're formatted, rather than formatting the whole
+    // Rustdoc at once
+    let prefix = options.line_prefix.as_deref().unwrap_or_default();
+    let mut written = false;
+    for section in output.render(options) {
+        // The Rustdoc starts with the first line of text, which isn't indented
+        let section = match written {
+            true => format_section(&section, options),
+            false => format_section(section.trim_start(), options),
+        };
+        if section.is_empty() {
+            continue;
+        }
+
+        if written {
+            writer
+                .write_str(&format!("\n{}\n", prefix.trim_end()))
+                .map_err(|_| ParseError::WriteFailed)?;
+        }
+        writer
+            .write_str(&section)
+            .map_err(|_| ParseError::WriteFailed)?;
+        written = true;
+    }
+
+    generator.stats.comments += 1;
+    debug!("generated the Rustdoc");
+
+    Ok(())
+}
+
+/// Runs the formatting passes over a section of the Rustdoc, like the wrapping of its lines.
+fn format_section(section: &str, options: &Options) -> String {
+    let mut result = format::normalize_whitespace(section);
+
+    if result.contains("-#") {
+        result = format::ordered_lists(&result);
+    }
+
+    if options.markdown_links {
+        result = format::markdown_links(&result);
+    }
+
+    if let Some(indent) = options.list_indent {
+        result = format::indent_list_items(&result, indent);
+    }
+
+    let prefix = options.line_prefix.as_deref().unwrap_or_default();
+    if let Some(width) = options.wrap {
+        let width = width.saturating_sub(prefix.chars().count());
+        result = format::wrap(&result, width, options.list_indent);
+    }
+
+    // After the wrapping, which can start lines with a `#`
+    if result.contains('#') {
+        result = format::escape_hashes(&result);
+    }
+
+    if !prefix.is_empty() {
+        result = format::prefix_lines(&result, prefix);
+    }
+
+    result
+}
+
+/// Where the generated Rustdoc goes.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+enum Target {
+    #[default]
+    Body,
+    Brief,
+    Quote,
+    Arguments,
+    Returns,
+    Retval(usize),
+    Errors,
+    Safety,
+    SeeAlso,
+    Examples,
+}
+
+/// The generated Rustdoc, split into the parts rendered separately.
+#[derive(Debug, Default)]
+struct Output {
+    target: Target,
+    brief: String,
+    body: String,
+    /// The block quote being generated, which is added to the body once it ends
+    quote: String,
+    /// The tag of the last block quote
+    quote_tag: Option<Tag>,
+    arguments: String,
+    returns: ReturnsSection,
+    /// The exceptions from `@throws`
+    errors: String,
+    /// The preconditions rendered with [`Options::pre_as_safety`]
+    safety: String,
+    see_also: String,
+    /// The code blocks gathered with [`Options::gather_examples`]
+    examples: String,
+}
+
+impl Output {
+    /// Creates the output, reserving `capacity` bytes for the body, where most of the Rustdoc goes.
+    fn with_capacity(capacity: usize) -> Self {
+        Self {
+            body: String::with_capacity(capacity),
+            ..Default::default()
+        }
+    }
+
+    fn set_target(&mut self, target: Target) {
+        if self.target == Target::Quote && target != Target::Quote {
+            let quote = std::mem::take(&mut self.quote);
+            let content = quote.trim_end_matches('\n');
+
+            // The admonitions that aren't block quotes (like `**Note:** Text.`) are paragraphs
+            match content.starts_with('>') {
+                true => self.body += &content.replace('\n', "\n> "),
+                false => self.body += content,
             }
-            "note" => String::from("> **Note:** "),
-            "since" => String::from("> Available since: "),
-            "deprecated" => String::from("> **Deprecated** "),
-            "remark" | "remarks" => String::from("> "),
-            "par" => String::from("# "),
-            "details" | "pre" | "post" => String::from("\n\n"),
-            "brief" | "short" => String::new(),
-            _ => String::new(),
-        },
-        (new_param, new_return, new_throw),
-    )
+            self.body += &quote[content.len()..];
+        }
+
+        self.target = target;
+    }
+
+    /// Starts the block quote of the tag, given its start (like `> **Note:** `). Consecutive block
+    /// quotes of different tags (like a note followed by a warning) are separated, as they would be
+    /// merged otherwise, and so are all the alerts, which can't be merged. The admonitions that
+    /// aren't block quotes are new paragraphs.
+    fn start_quote(&mut self, tag: &Tag, start: &str, style: &AdmonitionStyle) {
+        self.set_target(Target::Body);
+
+        let last_line = self.body.trim_end_matches('\n').rsplit('\n').next();
+        let merged = self.quote_tag.as_ref() == Some(tag) && *style != AdmonitionStyle::Alert;
+        let separated = match start.starts_with('>') {
+            true => !merged && last_line.is_some_and(|l| l.starts_with('>')),
+            false => !self.body.is_empty(),
+        };
+        if separated {
+            while !self.body.ends_with("\n\n") {
+                self.body.push('\n');
+            }
+        }
+
+        self.quote_tag = Some(tag.clone());
+        self.target = Target::Quote;
+    }
+
+    /// Starts a new entry (like a parameter) of the section, on its own line.
+    fn start_entry(&mut self, target: Target) {
+        self.set_target(target);
+
+        let current = self.current();
+        if !current.is_empty() && !current.ends_with('\n') {
+            current.push('\n');
+        }
+    }
+
+    fn push(&mut self, value: &str) {
+        match self.target {
+            Target::Retval(index) => self.returns.retvals[index].1 += value,
+            _ => *self.current() += value,
+        }
+    }
+
+    /// Removes the trailing spaces of the current text, keeping the ones before the list of return
+    /// values (if any), as it's inserted at their position.
+    fn trim_end(&mut self) {
+        let start = match self.target {
+            Target::Returns | Target::Retval(_) => self.returns.retvals_position.unwrap_or(0),
+            _ => 0,
+        };
+        let current = self.current();
+        let len = current.trim_end_matches(' ').len().max(start);
+        current.truncate(len);
+    }
+
+    /// The text of the current target, where the return values use the description of the section.
+    fn current(&mut self) -> &mut String {
+        match self.target {
+            Target::Brief => &mut self.brief,
+            Target::Quote => &mut self.quote,
+            Target::Arguments => &mut self.arguments,
+            Target::Returns | Target::Retval(_) => &mut self.returns.description,
+            Target::Errors => &mut self.errors,
+            Target::Safety => &mut self.safety,
+            Target::SeeAlso => &mut self.see_also,
+            Target::Examples => &mut self.examples,
+            Target::Body => &mut self.body,
+        }
+    }
+
+    /// Renders the sections in the order of [`Options::section_order`], followed by the sections
+    /// it leaves out in the default order. The sections are separated by a blank line.
+    fn render(mut self, options: &Options) -> Vec<String> {
+        self.set_target(Target::Body);
+
+        // The brief is the summary of the item, so it's always the first paragraph
+        let mut result = vec![self.brief.trim().to_string()];
+        let errors_title = match options.throws_as_errors {
+            true => "Errors",
+            false => "Throws",
+        };
+
+        let order = options.section_order.iter().chain(
+            Section::DEFAULT_ORDER
+                .iter()
+                .filter(|section| !options.section_order.contains(section)),
+        );
+        for section in order {
+            let (title, content) = match section {
+                Section::Description => (None, std::mem::take(&mut self.body)),
+                Section::Arguments => (Some("Arguments"), std::mem::take(&mut self.arguments)),
+                Section::Returns if self.returns.found => {
+                    (None, std::mem::take(&mut self.returns).render(options))
+                }
+                Section::Returns => continue,
+                Section::Errors => (Some(errors_title), std::mem::take(&mut self.errors)),
+                Section::Safety => (Some("Safety"), std::mem::take(&mut self.safety)),
+                Section::SeeAlso => (Some("See also"), std::mem::take(&mut self.see_also)),
+                Section::Examples => (Some("Examples"), std::mem::take(&mut self.examples)),
+            };
+
+            let content = content.trim_matches('\n');
+            if content.trim().is_empty() && (title.is_some() || content.is_empty()) {
+                continue;
+            }
+
+            let heading = title.map(|title| section_heading(title, options));
+            result.push(heading.unwrap_or_default() + content);
+        }
+
+        result
+    }
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
+/// The `# Returns` section, which is rendered once everything else is generated, to keep all the
+/// return-related content together.
+#[derive(Debug, Default)]
+struct ReturnsSection {
+    /// Whether a return tag was found
+    found: bool,
+    description: String,
+    retvals: Vec<(String, String)>,
+    /// The position of the list of return values in the description
+    retvals_position: Option<usize>,
+}
 
-    macro_rules! test_rustdoc {
+impl ReturnsSection {
+    fn render(mut self, options: &Options) -> String {
+        if let Some(position) = self.retvals_position {
+            let mut retvals = render_retvals(&self.retvals, options);
+            // The list starts on its own line, even after a description like `@return x @retval a`
+            let before = self.description[..position].trim_end_matches(' ');
+            if !before.is_empty() && !before.ends_with('\n') {
+                retvals.insert(0, '\n');
+            }
+            self.description.insert_str(position, &retvals);
+        }
+
+        let mut str = section_heading("Returns", options);
+        str += self.description.trim_start_matches(' ');
+        if !str.ends_with('\n') {
+            str.push('\n');
+        }
+
+        str
+    }
+}
+
+/// Removes the decoration of a comment (`* `) from the start of every line of the text, keeping
+/// the asterisks that aren't followed by a space (like the ones of `**bold**`).
+fn strip_decoration(text: &str) -> String {
+    text.split_inclusive('\n')
+        .map(|line| {
+            let content = line.trim_start_matches(' ');
+            let indent = &line[..line.len() - content.len()];
+
+            match content.strip_prefix('*') {
+                Some(rest) if rest.is_empty() || rest.starts_with([' ', '\n']) => {
+                    format!("{indent}{}", rest.strip_prefix(' ').unwrap_or(rest))
+                }
+                _ => line.into(),
+            }
+        })
+        .collect()
+}
+
+/// Whether the item following a tag (like `@param`) describes it.
+fn has_description(next: Option<&GrammarItem>) -> bool {
+    match next {
+        Some(GrammarItem::Text(text)) => {
+            let end = paragraph_end(text).unwrap_or(text.len());
+            !text[..end].trim().is_empty()
+        }
+        Some(GrammarItem::Notation { tag, .. }) => !tag.is_block_command(),
+        _ => false,
+    }
+}
+
+/// Whether the item following a `@par` means it doesn't have a title.
+pub(crate) fn is_untitled(next: Option<&GrammarItem>) -> bool {
+    match next {
+        Some(GrammarItem::Text(text)) => text.trim_start_matches(' ').starts_with('\n'),
+        Some(_) => false,
+        None => true,
+    }
+}
+
+/// Finds the end of the first paragraph of the text, which is followed by a blank line.
+pub(crate) fn paragraph_end(text: &str) -> Option<usize> {
+    let mut newlines = text.match_indices('\n').map(|(index, _)| index).peekable();
+
+    while let Some(index) = newlines.next() {
+        let next = *newlines.peek()?;
+        if text[index + 1..next].trim().is_empty() {
+            return Some(index + 1);
+        }
+    }
+
+    None
+}
+
+/// Renders the list of return values, merging the descriptions of the repeated ones.
+fn render_retvals(retvals: &[(String, String)], options: &Options) -> String {
+    let mut merged: Vec<(&str, Vec<&str>)> = vec![];
+    // The index in `merged` of each value
+    let mut indices: HashMap<&str, usize> = HashMap::with_capacity(retvals.len());
+
+    for (value, description) in retvals {
+        let description = description.trim();
+
+        match indices.get(value.as_str()) {
+            Some(&index) => {
+                let descriptions = &mut merged[index].1;
+                if !description.is_empty() && !descriptions.contains(&description) {
+                    descriptions.push(description);
+                }
+            }
+            None => {
+                indices.insert(value, merged.len());
+                merged.push((
+                    value,
+                    Vec::from_iter(Some(description).filter(|d| !d.is_empty())),
+                ));
+            }
+        }
+    }
+
+    let marker = options.list_marker.as_str();
+    merged
+        .into_iter()
+        .map(|(value, descriptions)| {
+            if !descriptions.is_empty() {
+                format!("{marker} `{value}` - {}\n", descriptions.join(" "))
+            } else if let Some(placeholder) = &options.missing_description {
+                format!("{marker} `{value}` - {placeholder}\n")
+            } else {
+                format!("{marker} `{value}`\n")
+            }
+        })
+        .collect()
+}
+
+/// Creates the heading of a section generated from the tags, like `# Arguments`.
+fn section_heading(title: &str, options: &Options) -> String {
+    match options.heading_style {
+        HeadingStyle::Heading => format!("{}{title}\n\n", heading_prefix(options, 0)),
+        HeadingStyle::Bold => format!("**{title}:**\n\n"),
+    }
+}
+
+/// Creates the prefix of a heading with [`Options::heading_level`], like `## `, nested `depth`
+/// levels further.
+fn heading_prefix(options: &Options, depth: usize) -> String {
+    format!(
+        "{} ",
+        "#".repeat((options.heading_level + depth).clamp(1, 6))
+    )
+}
+
+/// Returns the name of a `@param` without the C declarator around it, like `argv` for `argv[]`
+/// or `out` for `*out`. Variadic parameters (`...`) are kept as they are.
+pub(crate) fn param_name(name: &str) -> &str {
+    let stripped = name.trim_start_matches(['*', '&']);
+    let stripped = match stripped.find('[') {
+        Some(start) if stripped.ends_with(']') => &stripped[..start],
+        _ => stripped,
+    };
+
+    if stripped.is_empty() {
+        name
+    } else {
+        stripped
+    }
+}
+
+/// Whether the target of a `@ref` is a symbol (like `Foo::bar`, `Foo#bar` or `bar()`) rather than
+/// a label.
+fn is_symbol(target: &str) -> bool {
+    target.contains("::") || target.contains('#') || target.ends_with(')')
+}
+
+/// Returns the URL of the target of a Markdown link to a `@ref`, like `target` in
+/// `[text](@ref target)`, with the punctuation following it. The symbols and identifiers are
+/// intra-doc links, and the other labels (of sections, pages and anchors) anchors.
+pub(crate) fn link_target(target: &str, options: &Options) -> String {
+    let (_, target, trailing) = split_punctuation(target);
+    let is_identifier = target.starts_with(|c: char| c.is_alphabetic() || c == '_')
+        && target.chars().all(|c| c.is_alphanumeric() || c == '_');
+
+    let url = if let Some(url) = external_url(target, options) {
+        url.into()
+    } else if is_identifier || is_symbol(target) {
+        target[..target.find('(').unwrap_or(target.len())].replace('#', "::")
+    } else {
+        format!("#{target}")
+    };
+
+    url + trailing
+}
+
+/// Returns the URL of the target of a `@ref`, `@see` or `@throws` in the tag files or the external
+/// links of the options (if any).
+fn external_url<'a>(target: &str, options: &'a Options) -> Option<&'a str> {
+    options
+        .tag_files
+        .iter()
+        .find_map(|tag_file| tag_file.url(target))
+        .or_else(|| {
+            options
+                .external_links
+                .get(&tagfile::normalize(target))
+                .map(String::as_str)
+        })
+}
+
+/// Renders the start of an admonition (like `@note`) in the given style, which its text follows.
+fn admonition(tag: &Tag, style: &AdmonitionStyle) -> String {
+    let label = match tag {
+        Tag::Note => "Note",
+        Tag::Warning => "Warning",
+        Tag::Attention => "Attention",
+        Tag::Important => "Important",
+        Tag::Deprecated => "Deprecated",
+        Tag::Remark | Tag::Remarks => "Remark",
+        _ => "Available since",
+    };
+
+    let alert = match tag {
+        Tag::Note => Some("NOTE"),
+        Tag::Important => Some("IMPORTANT"),
+        Tag::Warning | Tag::Attention => Some("WARNING"),
+        Tag::Deprecated => Some("CAUTION"),
+        _ => None,
+    };
+
+    match (style, alert) {
+        (AdmonitionStyle::Alert, Some(kind)) if *tag == Tag::Deprecated => {
+            format!("> [!{kind}]\n**{label}** ")
+        }
+        (AdmonitionStyle::Alert, Some(kind)) => format!("> [!{kind}]\n"),
+        (AdmonitionStyle::Label, _) => format!("**{label}:** "),
+        (AdmonitionStyle::Template(template), _) => template.replace("{label}", label),
+        // The labels of `@deprecated` and `@since` have always been rendered this way
+        _ if *tag == Tag::Deprecated => format!("> **{label}** "),
+        _ if *tag == Tag::Since => format!("> {label}: "),
+        // The remarks have always been block quotes without a label
+        _ if matches!(tag, Tag::Remark | Tag::Remarks) => String::from("> "),
+        _ => format!("> **{label}:** "),
+    }
+}
+
+/// Reads the file of a diagram (like the one of a `@dotfile`) from the first of the
+/// [`Options::include_paths`] it's in.
+fn read_diagram(file: &str, options: &Options) -> Option<String> {
+    options
+        .include_paths
+        .iter()
+        .find_map(|path| std::fs::read_to_string(path.join(file)).ok())
+}
+
+/// Finds the emoji with the given shortcode (without the colons), preferring the custom ones. Only
+/// the custom ones are known without the `emoji` feature.
+fn find_emoji<'a>(name: &str, options: &'a Options) -> Option<&'a str> {
+    let emoji = options.custom_emojis.get(name).map(String::as_str);
+
+    #[cfg(feature = "emoji")]
+    let emoji = emoji.or_else(|| emojis::lookup(name));
+
+    emoji
+}
+
+/// Adds the HTML comment noting the tag a fragment was generated from, like `<!-- from @param line
+/// 12 -->`, for [`Options::provenance`].
+///
+/// The comment goes at the end of the first line of the fragment, as a comment starting a line
+/// would make the whole line an HTML block, and before the code fences, as it would be part of the
+/// code after them. The fences ending the code blocks don't get one.
+fn provenance(tag: &Tag, line: usize, fragment: String) -> String {
+    if matches!(tag, Tag::Endcode | Tag::Endverbatim) {
+        return fragment;
+    }
+
+    let comment = format!("<!-- from @{tag} line {line} -->");
+    let start = fragment.len() - fragment.trim_start_matches('\n').len();
+    if fragment[start..].starts_with("```") {
+        return format!("{}{comment}\n{}", &fragment[..start], &fragment[start..]);
+    }
+
+    let end = fragment[start..]
+        .find('\n')
+        .map_or(fragment.len(), |end| start + end);
+    let separator = match fragment[..end].ends_with(' ') || end == start {
+        true => "",
+        false => " ",
+    };
+
+    format!(
+        "{}{separator}{comment}{}",
+        &fragment[..end],
+        &fragment[end..]
+    )
+}
+
+/// Recreates the Doxygen of a notation, like `@emoji :smile:`.
+fn literal_notation(tag: &Tag, meta: &[String], params: &[String]) -> String {
+    let mut str = format!("@{tag}");
+
+    if !meta.is_empty() {
+        str += &format!("[{}]", meta.join(","));
+    }
+
+    // The space after the tag isn't kept by the parser, unlike the one after the parameters
+    if params.is_empty() {
+        str += " ";
+    } else {
+        for param in params {
+            str += &format!(" {param}");
+        }
+    }
+
+    str
+}
+
+/// The state of the generation shared by the notations of a comment.
+pub(crate) struct Generator<'a> {
+    options: &'a Options,
+    stats: &'a mut Stats,
+}
+
+impl<'a> Generator<'a> {
+    pub(crate) fn new(options: &'a Options, stats: &'a mut Stats) -> Self {
+        Self { options, stats }
+    }
+
+    pub(crate) fn notation(
+        &mut self,
+        tag: &Tag,
+        meta: &[String],
+        params: &[String],
+        line: usize,
+        span: &Range<usize>,
+        has_description: bool,
+    ) -> Result<String, ParseError> {
+        let options = self.options;
+        let first_param = || {
+            params.first().ok_or_else(|| ParseError::MissingParameter {
+                tag: tag.to_string(),
+                line,
+                span: span.clone(),
+            })
+        };
+
+        Ok(match tag {
+            Tag::Param => {
+                let mut str = String::new();
+                if let Some(param) = params.first().map(|param| param_name(param)) {
+                    let marker = options.list_marker.as_str();
+                    str += &match options.direction_style {
+                        _ if meta.is_empty() => format!("{marker} `{param}`"),
+                        DirectionStyle::Verbose => {
+                            format!("{marker} `{param}` (direction {})", meta.join(", "))
+                        }
+                        DirectionStyle::Compact => {
+                            format!("{marker} `{param}` [{}]", meta.join(","))
+                        }
+                        DirectionStyle::Omit => format!("{marker} `{param}`"),
+                    };
+
+                    if has_description {
+                        str += " -";
+                    } else if let Some(placeholder) = &options.missing_description {
+                        str += &format!(" - {placeholder}");
+                    }
+                }
+
+                str
+            }
+            Tag::A | Tag::E | Tag::Em => {
+                let (leading, word, trailing) = split_punctuation(first_param()?);
+                format!("{leading}_{word}_{trailing}")
+            }
+            Tag::B => {
+                let (leading, word, trailing) = split_punctuation(first_param()?);
+                format!("{leading}**{word}**{trailing}")
+            }
+            Tag::C | Tag::P => {
+                let (leading, word, trailing) = split_punctuation(first_param()?);
+                format!("{leading}`{word}`{trailing}")
+            }
+            Tag::Emoji => {
+                let word = first_param()?;
+                let name = word.replace(':', "");
+
+                if !options.expand_emojis {
+                    return Ok(format!(":{name}:"));
+                }
+
+                match find_emoji(&name, options) {
+                    Some(emoji) => emoji.to_string(),
+                    // Without the table of the emojis, the shortcodes are all kept as they are
+                    None if options.strict && cfg!(feature = "emoji") => {
+                        return Err(ParseError::UnknownEmoji {
+                            emoji: word.clone(),
+                            line,
+                            span: span.clone(),
+                        })
+                    }
+                    // Unknown shortcodes are kept, as Markdown renderers may know them
+                    None => format!(":{name}:"),
+                }
+            }
+            Tag::Sa | Tag::See => {
+                first_param()?;
+                params
+                    .iter()
+                    .map(|code_ref| {
+                        // Linking the URLs as code would be a broken intra-doc link
+                        if is_url(code_ref) {
+                            return autolink(code_ref);
+                        }
+
+                        if let Some(url) = external_url(code_ref, options) {
+                            return format!("[`{code_ref}`]({url})");
+                        }
+
+                        // Argument lists (like `()` or `<T>`) aren't part of the path of the item
+                        let path = &code_ref[..code_ref.find(['(', '<']).unwrap_or(code_ref.len())];
+                        if path.is_empty() || path == code_ref {
+                            format!("[`{code_ref}`]")
+                        } else {
+                            format!("[`{code_ref}`]({path})")
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            }
+            // Invisible, but can be linked to with `@ref`
+            Tag::Anchor => format!("<a id=\"{}\"></a>", first_param()?),
+            // The index of the Doxygen output has no equivalent in Rustdoc
+            Tag::Addindex => String::new(),
+            // The display of the Doxygen output can't be controlled in Rustdoc
+            _ if tag.is_display_control() => String::new(),
+            // The markup for other output formats is left out
+            _ if tag.is_foreign_region() => String::new(),
+            // The visibility is exposed in `RustdocComment::visibility` instead
+            _ if tag.visibility().is_some() => String::new(),
+            // The qualifiers are exposed in `RustdocComment::qualifiers` instead
+            Tag::Qualifier => {
+                first_param()?;
+                String::new()
+            }
+            Tag::Ref | Tag::Subpage => {
+                let (_, target, mut trailing) = split_punctuation(first_param()?);
+                let text = params.get(1);
+                if let Some(text_trailing) = params.get(2) {
+                    trailing = text_trailing;
+                }
+
+                // The symbols are linked with intra-doc links, and the labels (of sections,
+                // pages and anchors) as anchors
+                let external = external_url(target, options);
+                let is_symbol = *tag == Tag::Ref && is_symbol(target);
+                let url = if let Some(url) = external {
+                    url.into()
+                } else if is_symbol {
+                    target[..target.find('(').unwrap_or(target.len())].replace('#', "::")
+                } else {
+                    format!("#{target}")
+                };
+
+                let text = match text {
+                    Some(text) => text.clone(),
+                    None if is_symbol => format!("`{target}`"),
+                    None => target.into(),
+                };
+
+                format!("[{text}]({url}){trailing}")
+            }
+            // The `# Returns` section is rendered separately, see `ReturnsSection`
+            Tag::Retval => {
+                first_param()?;
+                String::new()
+            }
+            Tag::Returns | Tag::Return | Tag::Result => String::new(),
+            Tag::Throw | Tag::Throws | Tag::Exception => {
+                let exception = first_param()?;
+                let marker = options.list_marker.as_str();
+
+                // The exceptions mapped to Rust types are intra-doc links, the others may be in
+                // the tag files or the external links, like `std::runtime_error`
+                if let Some(error_type) = options.error_types.get(exception) {
+                    format!("{marker} [`{error_type}`] -")
+                } else if let Some(url) = external_url(exception, options) {
+                    format!("{marker} [`{exception}`]({url}) -")
+                } else {
+                    format!("{marker} [`{exception}`] -")
+                }
+            }
+            Tag::Note
+            | Tag::Warning
+            | Tag::Attention
+            | Tag::Important
+            | Tag::Since
+            | Tag::Deprecated
+            | Tag::Remark
+            | Tag::Remarks => admonition(tag, &options.admonition_style),
+            Tag::Par => heading_prefix(options, 0),
+            // The preconditions are rendered in the `# Safety` section
+            Tag::Pre if options.pre_as_safety => String::new(),
+            Tag::Code => {
+                let default_language = options.default_code_language.as_deref().unwrap_or("");
+                let language = params.first().map_or(default_language, |extension| {
+                    let extension = extension.trim_start_matches('.');
+                    options
+                        .code_languages
+                        .get(extension)
+                        .map_or(extension, String::as_str)
+                });
+
+                format!("\n```{language}\n")
+            }
+            Tag::Verbatim => format!(
+                "\n```{}\n",
+                options.verbatim_language.as_deref().unwrap_or("")
+            ),
+            Tag::Endcode | Tag::Endverbatim => String::from("```\n"),
+            Tag::Dotfile | Tag::Mscfile | Tag::Diafile => {
+                let (_, file, _) = split_punctuation(first_param()?);
+                let caption = params.get(1).filter(|caption| !caption.is_empty());
+                let language = match tag {
+                    Tag::Dotfile => Some("dot"),
+                    Tag::Mscfile => Some("msc"),
+                    _ => None,
+                };
+
+                match language.and_then(|_| read_diagram(file, options)) {
+                    Some(diagram) => {
+                        let caption = caption.map(|caption| format!("_{caption}_\n"));
+                        format!(
+                            "```{}\n{}\n```\n{}",
+                            language.unwrap_or_default(),
+                            diagram.trim_end(),
+                            caption.unwrap_or_default()
+                        )
+                    }
+                    None => {
+                        let text = caption.cloned().unwrap_or_else(|| format!("`{file}`"));
+                        format!("[{text}]({file})\n")
+                    }
+                }
+            }
+            Tag::Details | Tag::Pre | Tag::Post => String::from("\n\n"),
+            Tag::Brief | Tag::Short => String::new(),
+            // Structural commands only name the documented entity, which is already known
+            _ if tag.is_structural() => String::new(),
+            _ => {
+                *self.stats.unknown_tags.entry(tag.to_string()).or_default() += 1;
+
+                if options.strict {
+                    return Err(ParseError::UnknownTag {
+                        tag: tag.to_string(),
+                        line,
+                        span: span.clone(),
+                    });
+                }
+
+                if options.keep_unknown_tags {
+                    literal_notation(tag, meta, params)
+                } else {
+                    String::new()
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::options::{Limit, Limits, ListMarker};
+    use crate::tagfile::TagFile;
+
+    macro_rules! test_rustdoc {
         ($input:literal, $expected:literal) => {
             let result = $crate::generator::rustdoc($input.into()).unwrap();
             assert_eq!(result, $expected);
@@ -189,99 +1533,810 @@ mod test {
     }
 
     #[test]
-    fn unknown_annotation() {
-        test_rustdoc!("@thisdoesntexist Example doc", "Example doc");
+    fn unknown_annotation() {
+        test_rustdoc!("@thisdoesntexist Example doc", "Example doc");
+    }
+
+    #[test]
+    fn urls() {
+        test_rustdoc!(
+            "@brief See https://example.com/docs.",
+            "See <https://example.com/docs>."
+        );
+        test_rustdoc!(
+            "Example (see http://example.com/a_(b)), done",
+            "Example (see <http://example.com/a_(b)>), done"
+        );
+        test_rustdoc!(
+            "Mirrors: ftp://example.com, www.example.com.\nContact: mailto:support@example.com",
+            "Mirrors: <ftp://example.com>, [www.example.com](http://www.example.com).\nContact: <mailto:support@example.com>"
+        );
+    }
+
+    #[test]
+    fn markdown_links() {
+        let input = "See <a href=\"https://example.com\">the example</a>.";
+        assert_eq!(rustdoc(input.into()).unwrap(), input);
+
+        let options = Options {
+            markdown_links: true,
+            ..Default::default()
+        };
+        let result = rustdoc_with_options(input.into(), &options).unwrap();
+        assert_eq!(result, "See [the example](https://example.com).");
+    }
+
+    #[test]
+    fn code() {
+        test_rustdoc!(
+            "Example:\n@code{.py}\nprint(x)\n@endcode\nDone.",
+            "Example:\n\n```python\nprint(x)\n```\nDone."
+        );
+        test_rustdoc!("@code{.unparsed}\nx @endcode", "```text\nx\n```");
+        test_rustdoc!("@code{.lua}\nx\n@endcode", "```lua\nx\n```");
+
+        let mut options = Options::default();
+        options.code_languages.insert("h".into(), "cpp".into());
+
+        let result = rustdoc_with_options("@code{.h}\nx\n@endcode".into(), &options).unwrap();
+        assert_eq!(result, "```cpp\nx\n```");
+    }
+
+    #[test]
+    fn default_code_language() {
+        test_rustdoc!("@code\nx\n@endcode", "```text\nx\n```");
+
+        let options = Options {
+            default_code_language: Some("c".into()),
+            ..Default::default()
+        };
+        let result = rustdoc_with_options("@code\nx\n@endcode".into(), &options).unwrap();
+        assert_eq!(result, "```c\nx\n```");
+
+        let options = Options {
+            default_code_language: None,
+            ..Default::default()
+        };
+        let result = rustdoc_with_options("@code\nx\n@endcode".into(), &options).unwrap();
+        assert_eq!(result, "```\nx\n```");
+    }
+
+    #[test]
+    fn gather_examples() {
+        let options = Options {
+            gather_examples: true,
+            ..Default::default()
+        };
+
+        let result = rustdoc_with_options(
+            "@brief Adds.\n@code\nadd(1, 2);\n@endcode\n@param a A.\n@code{.c}\nadd(3, 4);\n@endcode"
+                .into(),
+            &options,
+        );
+        assert_eq!(
+            result.unwrap(),
+            "Adds.\n\n# Arguments\n\n* `a` - A.\n\n# Examples\n\n```text\nadd(1, 2);\n```\n\n```c\nadd(3, 4);\n```"
+        );
+    }
+
+    #[test]
+    fn code_indentation() {
+        test_rustdoc!(
+            "@code{.c}\nif (x) {\n    return  1;\n}\n@endcode",
+            "```c\nif (x) {\n    return  1;\n}\n```"
+        );
+        test_rustdoc!(
+            "Output:\n@verbatim\n    a   b\n  c\n@endverbatim",
+            "Output:\n\n```text\n    a   b\n  c\n```"
+        );
+    }
+
+    #[test]
+    fn verbatim_language() {
+        let options = Options {
+            verbatim_language: Some("console".into()),
+            ..Default::default()
+        };
+        let result = rustdoc_with_options("@verbatim\n$ ls\n@endverbatim".into(), &options);
+        assert_eq!(result.unwrap(), "```console\n$ ls\n```");
+
+        let options = Options {
+            verbatim_language: None,
+            ..Default::default()
+        };
+        let result = rustdoc_with_options("@verbatim\n$ ls\n@endverbatim".into(), &options);
+        assert_eq!(result.unwrap(), "```\n$ ls\n```");
+    }
+
+    #[test]
+    fn code_commands() {
+        test_rustdoc!(
+            "@code{.c}\n/** @param x The value */\nprintf(\"%d\\n\", x);\n@endcode\n@param x The value",
+            "```c\n/** @param x The value */\nprintf(\"%d\\n\", x);\n```\n\n# Arguments\n\n* `x` - The value"
+        );
+    }
+
+    #[test]
+    fn from_bytes() {
+        let result = rustdoc_from_bytes(b"@brief Na\xefve @c x", &Options::default()).unwrap();
+        assert_eq!(result, "Na\u{FFFD}ve `x`");
+    }
+
+    #[test]
+    fn writes_into_sink() {
+        struct FailingWriter;
+
+        impl fmt::Write for FailingWriter {
+            fn write_str(&mut self, _: &str) -> fmt::Result {
+                Err(fmt::Error)
+            }
+        }
+
+        let mut rustdoc = String::from("/// ");
+        rustdoc_to(&mut rustdoc, "@brief Example".into()).unwrap();
+        assert_eq!(rustdoc, "/// Example");
+
+        let result = rustdoc_to(&mut FailingWriter, "@brief Example".into());
+        assert!(matches!(result, Err(ParseError::WriteFailed)));
+
+        // The sections are written one at a time
+        #[derive(Default)]
+        struct Writes(Vec<String>);
+
+        impl fmt::Write for Writes {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                self.0.push(s.into());
+                Ok(())
+            }
+        }
+
+        let mut writes = Writes::default();
+        let options = Options {
+            line_prefix: Some("/// ".into()),
+            ..Default::default()
+        };
+        rustdoc_to_with_options(&mut writes, "@brief Example\n@param a A".into(), &options)
+            .unwrap();
+        assert_eq!(
+            writes.0.concat(),
+            "/// Example\n///\n/// # Arguments\n///\n/// * `a` - A"
+        );
+        assert_eq!(writes.0.len(), 3);
+
+        let mut rustdoc = vec![];
+        rustdoc_to_io(&mut rustdoc, "@brief Example\n@return x".into()).unwrap();
+        assert_eq!(rustdoc, b"Example\n\n# Returns\n\nx");
+        assert!(rustdoc_to_io(&mut [0u8; 4].as_mut_slice(), "@brief Example".into()).is_err());
+    }
+
+    #[test]
+    fn strict_unknown_annotation() {
+        let options = Options {
+            strict: true,
+            ..Default::default()
+        };
+
+        let result = rustdoc_with_options(
+            "@brief Example doc\n@thisdoesntexist Example doc".into(),
+            &options,
+        );
+        assert!(matches!(
+            result,
+            Err(ParseError::UnknownTag { tag, line: 2, span }) if tag == "thisdoesntexist" && span == (19..35)
+        ));
+
+        let result = rustdoc_with_options("@brief Example @c doc\n@class Example".into(), &options);
+        assert_eq!(result.unwrap(), "Example `doc`");
+    }
+
+    #[test]
+    fn keep_unknown_annotation() {
+        let options = Options {
+            keep_unknown_tags: true,
+            ..Default::default()
+        };
+
+        let result = rustdoc_with_options("@brief Example\n@mytag foo".into(), &options);
+        assert_eq!(result.unwrap(), "Example\n@mytag foo");
+    }
+
+    #[cfg(feature = "emoji")]
+    #[test]
+    fn lossy() {
+        let (result, warnings) = rustdoc_lossy(
+            "@brief Example @c\n@retval\n@param[up] x Hi @emoji :nope:".into(),
+            &Options::default(),
+        );
+        assert_eq!(result, "Example @c\n\n@retval @param[up] x Hi :nope:");
+        assert_eq!(warnings.len(), 4);
+        assert!(matches!(
+            &warnings[3],
+            ParseError::UnknownEmoji { emoji, line: 3, span } if emoji == ":nope:" && *span == (42..55)
+        ));
+
+        assert!(matches!(
+            rustdoc("@b".into()),
+            Err(ParseError::MissingParameter { tag, line: 1, span }) if tag == "b" && span == (0..2)
+        ));
+    }
+
+    #[test]
+    fn fences_after_return_values() {
+        let input = "@return x @retval a @endcode";
+        assert!(rustdoc(input.into()).is_ok());
+        rustdoc_lossy(input.into(), &Options::default());
+        rustdoc_partial(input.into(), &Options::default());
+    }
+
+    #[test]
+    fn wrap() {
+        let options = Options {
+            wrap: Some(24),
+            ..Default::default()
+        };
+
+        let result = rustdoc_with_options(
+            "@brief Creates a new example.\n@param example The example to use when creating it."
+                .into(),
+            &options,
+        );
+        assert_eq!(
+            result.unwrap(),
+            "Creates a new example.\n\n# Arguments\n\n* `example` - The\n  example to use when\n  creating it."
+        );
+    }
+
+    #[test]
+    fn line_prefix() {
+        let options = Options {
+            line_prefix: Some("/// ".into()),
+            wrap: Some(24),
+            ..Default::default()
+        };
+        let result = rustdoc_with_options(
+            "@brief Example\n@param example The example parameter.".into(),
+            &options,
+        );
+        assert_eq!(
+            result.unwrap(),
+            "/// Example\n///\n/// # Arguments\n///\n/// * `example` - The\n///   example parameter."
+        );
+    }
+
+    #[test]
+    fn provenance() {
+        let options = Options {
+            provenance: true,
+            ..Default::default()
+        };
+        let result = rustdoc_with_options(
+            "@brief Example\n@param a The @c value.\n@note Careful.\n@code\nx\n@endcode".into(),
+            &options,
+        );
+        assert_eq!(
+            result.unwrap(),
+            "<!-- from @brief line 1 -->Example\n\n> **Note:** <!-- from @note line 3 -->Careful.\n\n<!-- from @code line 4 -->\n```text\nx\n```\n\n# Arguments\n\n* `a` - <!-- from @param line 2 --> The `value`. <!-- from @c line 2 -->"
+        );
+    }
+
+    #[test]
+    fn diagrams() {
+        let root = std::env::temp_dir().join(format!("doxygen-rs-diagrams-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("graph.dot"), "digraph { a -> b }\n").unwrap();
+        std::fs::write(root.join("chart.msc"), "msc { a, b; a => b; }\n").unwrap();
+
+        let options = Options {
+            include_paths: vec![root.join("missing"), root.clone()],
+            ..Default::default()
+        };
+        let result = rustdoc_with_options(
+            "@brief Flow
+@dotfile graph.dot \"The graph\"
+@mscfile chart.msc
+@dotfile other.dot
+@diafile a.dia \"Layout\""
+                .into(),
+            &options,
+        );
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(
+            result.unwrap(),
+            "Flow\n\n```dot\ndigraph { a -> b }\n```\n_The graph_\n\n```msc\nmsc { a, b; a => b; }\n```\n\n[`other.dot`](other.dot)\n\n[Layout](a.dia)"
+        );
+    }
+
+    #[test]
+    fn list_style() {
+        let options = Options {
+            list_marker: ListMarker::Dash,
+            list_indent: Some(4),
+            ..Default::default()
+        };
+
+        let result = rustdoc_with_options(
+            "@param a The\nvalue.\n@retval 0 Zero.\n@throws Error Fails.".into(),
+            &options,
+        );
+        assert_eq!(
+            result.unwrap(),
+            "# Arguments\n\n- `a` - The\n    value.\n\n# Returns\n\n- `0` - Zero.\n\n# Throws\n\n- [`Error`] - Fails."
+        );
+    }
+
+    #[test]
+    fn tag_aliases() {
+        let options = Options {
+            tag_aliases: HashMap::from([
+                ("returns_value".into(), "retval".into()),
+                ("argument".into(), "param".into()),
+            ]),
+            ..Default::default()
+        };
+
+        let result = rustdoc_with_options(
+            "@argument[in] a The value.\n@returns_value 0 Success.".into(),
+            &options,
+        );
+        assert_eq!(
+            result.unwrap(),
+            "# Arguments\n\n* `a` (direction in) - The value.\n\n# Returns\n\n* `0` - Success."
+        );
+    }
+
+    #[test]
+    fn normalizes_whitespace() {
+        test_rustdoc!("  @brief Example.   \n@returns   ", "Example.\n\n# Returns");
+    }
+
+    #[test]
+    fn bold_headings() {
+        let options = Options {
+            heading_style: HeadingStyle::Bold,
+            ..Default::default()
+        };
+
+        let result = rustdoc_with_options(
+            "@param example An example.\n@returns Nothing.".into(),
+            &options,
+        );
+        assert_eq!(
+            result.unwrap(),
+            "**Arguments:**\n\n* `example` - An example.\n\n**Returns:**\n\nNothing."
+        );
+    }
+
+    #[test]
+    fn heading_level() {
+        let options = Options {
+            heading_level: 2,
+            ..Default::default()
+        };
+
+        let result = rustdoc_with_options(
+            "@par Example\nThis is an example.\n@param example An example.".into(),
+            &options,
+        );
+        assert_eq!(
+            result.unwrap(),
+            "## Example\nThis is an example.\n\n## Arguments\n\n* `example` - An example."
+        );
+    }
+
+    #[test]
+    fn param_with_direction() {
+        test_rustdoc!(
+            "@param[in] example This insane thing.",
+            "# Arguments\n\n* `example` (direction in) - This insane thing."
+        );
+
+        test_rustdoc!(
+            "@param[in,out] example This insane thing.",
+            "# Arguments\n\n* `example` (direction in, out) - This insane thing."
+        );
+
+        test_rustdoc!(
+            "@param[out,in] example This insane thing.",
+            "# Arguments\n\n* `example` (direction in, out) - This insane thing."
+        );
+    }
+
+    #[test]
+    fn param_direction_style() {
+        let options = Options {
+            direction_style: DirectionStyle::Compact,
+            ..Default::default()
+        };
+
+        let result = rustdoc_with_options("@param[in,out] example An example.".into(), &options);
+        assert_eq!(
+            result.unwrap(),
+            "# Arguments\n\n* `example` [in,out] - An example."
+        );
+
+        let options = Options {
+            direction_style: DirectionStyle::Omit,
+            ..Default::default()
+        };
+
+        let result = rustdoc_with_options("@param[in,out] example An example.".into(), &options);
+        assert_eq!(result.unwrap(), "# Arguments\n\n* `example` - An example.");
+    }
+
+    #[test]
+    fn param_without_direction() {
+        test_rustdoc!(
+            "@param example This is definitively an example!",
+            "# Arguments\n\n* `example` - This is definitively an example!"
+        );
+    }
+
+    #[test]
+    fn param_without_description() {
+        test_rustdoc!(
+            "@param example\n@param other Other.",
+            "# Arguments\n\n* `example`\n* `other` - Other."
+        );
+
+        let options = Options {
+            missing_description: Some("Undocumented.".into()),
+            ..Default::default()
+        };
+
+        let (result, warnings) = rustdoc_lossy("@param example\n@retval 0".into(), &options);
+        assert_eq!(
+            result,
+            "# Arguments\n\n* `example` - Undocumented.\n\n# Returns\n\n* `0` - Undocumented."
+        );
+        assert!(matches!(
+            &warnings[..],
+            [ParseError::MissingDescription { name, line: 1, .. }] if name == "example"
+        ));
+
+        let (_, warnings) = rustdoc_lossy("Text\n@param".into(), &Options::default());
+        assert_eq!(
+            warnings,
+            [ParseError::MissingParamName {
+                tag: "param".into(),
+                line: 2,
+                span: 5..11,
+            }]
+        );
+    }
+
+    #[test]
+    fn multiple_params() {
+        test_rustdoc!(
+            "@param example1 This is the first example\n@param[out] example2 This is the second example\n@param[in] example3 This is the third example.",
+            "# Arguments\n\n* `example1` - This is the first example\n* `example2` (direction out) - This is the second example\n* `example3` (direction in) - This is the third example."
+        );
+    }
+
+    #[test]
+    fn declarator_params() {
+        test_rustdoc!(
+            "@param fmt The format.\n@param ... The values.\n@param argv[] The arguments.\n@param[out] *out The output.\n@param matrix[4][4] The matrix.",
+            "# Arguments\n\n* `fmt` - The format.\n* `...` - The values.\n* `argv` - The arguments.\n* `out` (direction out) - The output.\n* `matrix` - The matrix."
+        );
+    }
+
+    #[test]
+    fn punctuation_after_styled_words() {
+        test_rustdoc!(
+            "Returns @c NULL, @b never @p ptr. (or @c f(x))",
+            "Returns `NULL`, **never** `ptr`. (or `f(x)`)"
+        );
+        test_rustdoc!("Ellipsis @c ...", "Ellipsis `...`");
+        test_rustdoc!(
+            "It's @a (really) @em \"great\", @b [sure]!",
+            "It's (_really_) \"_great_\", [**sure**]!"
+        );
+    }
+
+    #[test]
+    fn italics() {
+        test_rustdoc!(
+            "This @a thing is without a doubt @e great. @em And you won't tell me otherwise.",
+            "This _thing_ is without a doubt _great_. _And_ you won't tell me otherwise."
+        );
+    }
+
+    #[test]
+    fn bold() {
+        test_rustdoc!("This is a @b bold claim.", "This is a **bold** claim.");
+    }
+
+    #[test]
+    fn code_inline() {
+        test_rustdoc!(
+            "@c u8 is not the same as @p u32",
+            "`u8` is not the same as `u32`"
+        );
+    }
+
+    #[cfg(feature = "emoji")]
+    #[test]
+    fn emoji() {
+        test_rustdoc!("@emoji :relieved: @emoji :ok_hand:", "😌 👌");
+        test_rustdoc!("@emoji :thumbsup: @emoji :t-rex:", "👍 🦖");
+        test_rustdoc!("Hi @emoji :not_an_emoji:", "Hi :not_an_emoji:");
+
+        let options = Options {
+            strict: true,
+            ..Default::default()
+        };
+        let result = rustdoc_with_options("@emoji :not_an_emoji:".into(), &options);
+        assert!(matches!(
+            result,
+            Err(ParseError::UnknownEmoji { line: 1, .. })
+        ));
+    }
+
+    #[cfg(not(feature = "emoji"))]
+    #[test]
+    fn emoji_without_table() {
+        let options = Options {
+            strict: true,
+            custom_emojis: HashMap::from([("ship".into(), "🚢".into())]),
+            ..Default::default()
+        };
+
+        let result = rustdoc_with_options("@emoji :ship: @emoji :smile:".into(), &options);
+        assert_eq!(result.unwrap(), "🚢 :smile:");
+    }
+
+    #[test]
+    fn custom_emojis() {
+        let mut options = Options::default();
+        options.custom_emojis.insert("ship".into(), "🚀".into());
+        options.custom_emojis.insert("smile".into(), ":)".into());
+
+        let result = rustdoc_with_options("@emoji :ship: @emoji :smile:".into(), &options);
+        assert_eq!(result.unwrap(), "🚀 :)");
+
+        options.expand_emojis = false;
+        let result = rustdoc_with_options("@emoji :ship: @emoji smile".into(), &options);
+        assert_eq!(result.unwrap(), ":ship: :smile:");
     }
 
+    #[cfg(feature = "emoji")]
     #[test]
-    fn param_with_direction() {
+    fn text_styling() {
         test_rustdoc!(
-            "@param[in] example This insane thing.",
-            "# Arguments\n\n* `example` (direction in) - This insane thing."
+            "This is from @a Italy. ( @b I @c hope @emoji :pray: )",
+            "This is from _Italy_. ( **I** `hope` 🙏 )"
         );
+    }
 
+    #[test]
+    fn brief() {
         test_rustdoc!(
-            "@param[in,out] example This insane thing.",
-            "# Arguments\n\n* `example` (direction in, out) - This insane thing."
+            "@brief This function does things.\n@short This function also does things.",
+            "This function does things.\nThis function also does things."
         );
+    }
 
+    #[test]
+    fn see_also() {
         test_rustdoc!(
-            "@param[out,in] example This insane thing.",
-            "# Arguments\n\n* `example` (direction in, out) - This insane thing."
+            "@sa random_thing @see random_thing_2",
+            "# See also\n\n[`random_thing`]\n[`random_thing_2`]"
+        );
+        test_rustdoc!("@sa foo bar baz", "# See also\n\n[`foo`], [`bar`], [`baz`]");
+        test_rustdoc!(
+            "@see a, b,c\nText",
+            "# See also\n\n[`a`], [`b`], [`c`]\nText"
+        );
+        test_rustdoc!(
+            "@see https://example.com/docs, www.example.com",
+            "# See also\n\n<https://example.com/docs>, [www.example.com](http://www.example.com)"
+        );
+        test_rustdoc!(
+            "@see foo_init(), Foo::bar(int, char*) Vec<T>",
+            "# See also\n\n[`foo_init()`](foo_init), [`Foo::bar(int, char*)`](Foo::bar), [`Vec<T>`](Vec)"
         );
     }
 
     #[test]
-    fn param_without_direction() {
+    fn references() {
         test_rustdoc!(
-            "@param example This is definitively an example!",
-            "# Arguments\n\n* `example` - This is definitively an example!"
+            "See @ref some_label \"Human readable title\".",
+            "See [Human readable title](#some_label)."
+        );
+        test_rustdoc!("See @ref intro.", "See [intro](#intro).");
+        test_rustdoc!(
+            "@ref Foo::bar() \"the bar\" and @ref Foo#baz",
+            "[the bar](Foo::bar) and [`Foo#baz`](Foo::baz)"
+        );
+        test_rustdoc!(
+            "@subpage intro \"Introduction\"\n@subpage usage",
+            "[Introduction](#intro)\n[usage](#usage)"
+        );
+        test_rustdoc!(
+            "See [the setup](@ref getting-started) and [`bar`](\\ref Foo::bar()).",
+            "See [the setup](#getting-started) and [`bar`](Foo::bar)."
+        );
+        test_rustdoc!(
+            "Call [the initializer](@ref foo_init) first.",
+            "Call [the initializer](foo_init) first."
         );
     }
 
     #[test]
-    fn multiple_params() {
-        test_rustdoc!(
-            "@param example1 This is the first example\n@param[out] example2 This is the second example\n@param[in] example3 This is the third example.",
-            "# Arguments\n\n* `example1` - This is the first example\n* `example2` (direction out) - This is the second example\n* `example3` (direction in) - This is the third example."
+    fn external_references() {
+        let xml = r#"<tagfile>
+  <compound kind="file">
+    <name>foo.h</name>
+    <filename>foo_8h.html</filename>
+    <member kind="function">
+      <name>foo_init</name>
+      <anchorfile>foo_8h.html</anchorfile>
+      <anchor>a1</anchor>
+    </member>
+  </compound>
+  <compound kind="page">
+    <name>intro</name>
+    <filename>intro.html</filename>
+  </compound>
+</tagfile>"#;
+        let options = Options {
+            tag_files: vec![TagFile::parse(xml, "https://foo.org")],
+            ..Default::default()
+        };
+
+        let result = rustdoc_with_options(
+            "See @ref intro and @ref setup.\n@see foo_init(), bar()".into(),
+            &options,
+        )
+        .unwrap();
+        assert_eq!(
+            result,
+            "See [intro](https://foo.org/intro.html) and [setup](#setup).\n\n# See also\n\n[`foo_init()`](https://foo.org/foo_8h.html#a1), [`bar()`](bar)"
         );
     }
 
     #[test]
-    fn italics() {
+    fn standard_links() {
         test_rustdoc!(
-            "This @a thing is without a doubt @e great. @em And you won't tell me otherwise.",
-            "This _thing_ is without a doubt _great._ _And_ you won't tell me otherwise."
+            "@see std::vector<int>, mmap(), open()\nSets @ref errno.",
+            "# See also\n\n[`std::vector<int>`](https://en.cppreference.com/w/cpp/container/vector), [`mmap()`](https://man7.org/linux/man-pages/man2/mmap.2.html), [`open()`](open)\nSets [errno](https://man7.org/linux/man-pages/man3/errno.3.html)."
+        );
+        // The names generic enough to be the crate's own are linked as intra-doc links
+        test_rustdoc!(
+            "@see free(), FILE, size_t",
+            "# See also\n\n[`free()`](free), [`FILE`], [`size_t`]"
+        );
+
+        let options = Options {
+            external_links: HashMap::from([("foo".into(), "https://foo.org".into())]),
+            ..Default::default()
+        };
+        let result = rustdoc_with_options("@see foo(), mmap()".into(), &options).unwrap();
+        assert_eq!(
+            result,
+            "# See also\n\n[`foo()`](https://foo.org), [`mmap()`](mmap)"
         );
     }
 
     #[test]
-    fn bold() {
-        test_rustdoc!("This is a @b bold claim.", "This is a **bold** claim.");
+    fn display_control_commands() {
+        test_rustdoc!(
+            "@brief Modes\n@showenumvalues\nThe @hiderefs modes.\n@showinitializer",
+            "Modes\nThe modes."
+        );
+
+        let options = Options {
+            strict: true,
+            ..Default::default()
+        };
+        let (result, warnings) = rustdoc_lossy("Text @showrefs here.".into(), &options);
+        assert_eq!(result, "Text here.");
+        assert!(matches!(
+            &warnings[..],
+            [ParseError::IgnoredTag { tag, line: 1, span }] if tag == "showrefs" && *span == (5..14)
+        ));
+        assert!(rustdoc_with_options("@hideenumvalues Text.".into(), &options).is_ok());
+        assert!(rustdoc_lossy("@showrefby".into(), &Options::default())
+            .1
+            .is_empty());
     }
 
     #[test]
-    fn code_inline() {
+    fn unmatched_code_block_ends() {
+        test_rustdoc!("a @endcode b", "a b");
         test_rustdoc!(
-            "@c u8 is not the same as @p u32",
-            "`u8` is not the same as `u32`"
+            "@code\nx\n@endcode\nb @endverbatim c",
+            "```text\nx\n```\nb c"
         );
+
+        let (result, warnings) = rustdoc_lossy("a\n@endverbatim\nb".into(), &Options::default());
+        assert_eq!(result, "a\nb");
+        assert!(matches!(
+            warnings.as_slice(),
+            [ParseError::UnmatchedTag { tag, line: 2, span }] if tag == "endverbatim" && *span == (2..14)
+        ));
     }
 
     #[test]
-    fn emoji() {
-        test_rustdoc!("@emoji :relieved: @emoji :ok_hand:", "😌 👌");
+    fn commands_after_words() {
+        test_rustdoc!(
+            "Contact dev@code.example.com\n@param x The x.\n@return @b bold",
+            "Contact dev@code.example.com\n\n# Arguments\n\n* `x` - The x.\n\n# Returns\n\n**bold**"
+        );
+        test_rustdoc!(
+            "Open C:\\verbatim\\file with @c open",
+            "Open C:\\verbatim\\file with `open`"
+        );
     }
 
     #[test]
-    fn text_styling() {
+    fn index_commands() {
         test_rustdoc!(
-            "This is from @a Italy. ( @b I @c hope @emoji :pray: )",
-            "This is from _Italy._ ( **I** `hope` 🙏 )"
+            "@anchor setup Setup.\n@addindex setup, configuration\nText.",
+            "<a id=\"setup\"></a> Setup.\n\nText."
         );
+
+        let (result, warnings) = rustdoc_lossy("Text.\n@addindex text".into(), &Options::default());
+        assert_eq!(result, "Text.");
+        assert!(matches!(
+            warnings[..],
+            [ParseError::IgnoredTag { line: 2, .. }]
+        ));
     }
 
     #[test]
-    fn brief() {
+    fn ordered_lists() {
         test_rustdoc!(
-            "@brief This function does things.\n@short This function also does things.",
-            "This function does things.\nThis function also does things."
+            "Steps:\n -# Open.\n -# Write @c data.\n\nDone.",
+            "Steps:\n1. Open.\n2. Write `data`.\n\nDone."
         );
     }
 
     #[test]
-    fn see_also() {
+    fn nested_lists() {
         test_rustdoc!(
-            "@sa random_thing @see random_thing_2",
-            "[`random_thing`] [`random_thing_2`]"
+            "Items:\n - First\n   - Nested @c item\n - Second",
+            "Items:\n - First\n   - Nested `item`\n - Second"
+        );
+        test_rustdoc!(
+            "Steps:\n -# Open.\n    -# Check.\n -# Close.",
+            "Steps:\n1. Open.\n   1. Check.\n2. Close."
         );
     }
 
+    #[test]
+    fn visibility_sections() {
+        test_rustdoc!("@privatesection\nInternal state.", "Internal state.");
+
+        let (_, warnings) = rustdoc_lossy("@private Hidden.".into(), &Options::default());
+        assert!(matches!(
+            &warnings[..],
+            [ParseError::IgnoredTag { tag, line: 1, .. }] if tag == "private"
+        ));
+    }
+
     #[test]
     fn deprecated() {
         test_rustdoc!(
             "@deprecated This function is deprecated!\n@param example_1 Example 1.",
-            "> **Deprecated** This function is deprecated!\n# Arguments\n\n* `example_1` - Example 1."
+            "> **Deprecated** This function is deprecated!\n\n# Arguments\n\n* `example_1` - Example 1."
+        );
+    }
+
+    #[test]
+    fn multiline_brief() {
+        test_rustdoc!(
+            "@brief Frees the buffer\nand its contents.\n\nDetails.\n@brief Short.\n@param a A.",
+            "Frees the buffer\nand its contents.\nShort.\n\nDetails.\n\n# Arguments\n\n* `a` - A."
+        );
+    }
+
+    #[test]
+    fn brief_is_summary() {
+        test_rustdoc!(
+            "@param example An example.\n@brief Uses the example.",
+            "Uses the example.\n\n# Arguments\n\n* `example` - An example."
         );
     }
 
@@ -289,7 +2344,7 @@ mod test {
     fn details() {
         test_rustdoc!(
             "@brief This function is insane!\n@details This is an insane function because its functionality and performance is quite astonishing.",
-            "This function is insane!\n\n\nThis is an insane function because its functionality and performance is quite astonishing."
+            "This function is insane!\n\nThis is an insane function because its functionality and performance is quite astonishing."
         );
     }
 
@@ -301,6 +2356,14 @@ mod test {
         );
     }
 
+    #[test]
+    fn untitled_paragraph() {
+        test_rustdoc!(
+            "This is a function.\n@par\nThis is another paragraph.",
+            "This is a function.\n\nThis is another paragraph."
+        );
+    }
+
     #[test]
     fn remark() {
         test_rustdoc!(
@@ -309,6 +2372,84 @@ mod test {
         );
     }
 
+    #[test]
+    fn consecutive_quotes() {
+        test_rustdoc!(
+            "@note A\n@warning B\nC\n\nD",
+            "> **Note:** A\n\n> **Warning:** B\n> C\n\nD"
+        );
+        test_rustdoc!(
+            "@deprecated Old\n@note New",
+            "> **Deprecated** Old\n\n> **Note:** New"
+        );
+    }
+
+    #[test]
+    fn structural_commands() {
+        test_rustdoc!(
+            "@protocol NSCopying Copies objects.\n\n@package com.example.util\nUtilities.\n\n@category NSString(Utils) Helpers.\n@def X Definition.\n@weakgroup io (Input and output)\nGrouped.",
+            "Copies objects.\n\nUtilities.\n\nHelpers.\nDefinition.\nGrouped."
+        );
+        test_rustdoc!(
+            "Reads a file.\n@ingroup k\nThe details @ingroup io files\nfollow.",
+            "Reads a file.\nThe details\nfollow."
+        );
+    }
+
+    #[test]
+    fn foreign_regions() {
+        test_rustdoc!(
+            "@brief A @docbookonly <para>@b x {y}</para> @enddocbookonly b.\n\n@docbookonly\n<note>\n  <para>Note</para>\n</note>\n@enddocbookonly\nAfter.",
+            "A b.\n\nAfter."
+        );
+
+        // The lines of the regions are still counted
+        let (_, warnings) = rustdoc_lossy(
+            "@docbookonly\n<para/>\n@enddocbookonly\n@param x".into(),
+            &Options::default(),
+        );
+        assert_eq!(
+            warnings,
+            vec![ParseError::MissingDescription {
+                tag: "param".into(),
+                name: "x".into(),
+                line: 4,
+                span: 37..45,
+            }]
+        );
+    }
+
+    #[test]
+    fn preprocessor_lines() {
+        test_rustdoc!(
+            "@brief Includes\n#include <x.h>\n# Usage\nSet #FOO.\n@code\n#define FOO 1\n@endcode",
+            "Includes\n\\#include <x.h>\n# Usage\nSet #FOO.\n\n```text\n#define FOO 1\n```"
+        );
+
+        let options = Options {
+            wrap: Some(12),
+            ..Default::default()
+        };
+        let result = rustdoc_with_options("Uses the #define macro.".into(), &options);
+        assert_eq!(result.unwrap(), "Uses the\n\\#define\nmacro.");
+    }
+
+    #[test]
+    fn qualifiers() {
+        test_rustdoc!(
+            "@brief Opens @qualifier static a file.\n@qualifier \"(thread safe)\"\nDetails @qualifier x",
+            "Opens a file.\nDetails"
+        );
+    }
+
+    #[test]
+    fn important() {
+        test_rustdoc!(
+            "@brief Example\n@important Call it\nonce.\n\nDetails.",
+            "Example\n\n> **Important:** Call it\n> once.\n\nDetails."
+        );
+    }
+
     #[test]
     fn returns() {
         test_rustdoc!(
@@ -343,6 +2484,156 @@ mod test {
         );
     }
 
+    #[test]
+    fn repeated_return_values() {
+        test_rustdoc!(
+            "@retval 0 Success.\n@retval -1 Failure.\n\nMore details.\n@retval 0 Success.\n@retval -1 Out of memory.",
+            "More details.\n\n# Returns\n\n* `0` - Success.\n* `-1` - Failure. Out of memory."
+        );
+    }
+
+    #[test]
+    fn contiguous_returns() {
+        test_rustdoc!(
+            "@brief Example.\n@return The result.\n\nThis is an example.\n@retval 0 Success.\n@retval -1 Failure.",
+            "Example.\n\nThis is an example.\n\n# Returns\n\nThe result.\n* `0` - Success.\n* `-1` - Failure."
+        );
+        test_rustdoc!("@return x @retval a", "# Returns\n\nx\n* `a`");
+    }
+
+    #[test]
+    fn throws_as_errors() {
+        let options = Options {
+            throws_as_errors: true,
+            ..Default::default()
+        };
+
+        let result = rustdoc_with_options(
+            "@throws std::io::bonk When INSANE things happen.".into(),
+            &options,
+        );
+        assert_eq!(
+            result.unwrap(),
+            "# Errors\n\n* [`std::io::bonk`] - When INSANE things happen."
+        );
+    }
+
+    #[test]
+    fn error_types() {
+        let options = Options {
+            throws_as_errors: true,
+            error_types: HashMap::from([(
+                "std::runtime_error".into(),
+                "crate::Error::Runtime".into(),
+            )]),
+            ..Default::default()
+        };
+
+        let result = rustdoc_with_options(
+            "@throws std::runtime_error On failure.\n@throws std::bad_alloc Out of memory.".into(),
+            &options,
+        );
+        assert_eq!(
+            result.unwrap(),
+            "# Errors\n\n* [`crate::Error::Runtime`] - On failure.\n* [`std::bad_alloc`](https://en.cppreference.com/w/cpp/memory/new/bad_alloc) - Out of memory."
+        );
+    }
+
+    #[test]
+    fn external_exceptions() {
+        test_rustdoc!(
+            "@throws std::runtime_error On failure.\n@throws Error Otherwise.",
+            "# Throws\n\n* [`std::runtime_error`](https://en.cppreference.com/w/cpp/error/runtime_error) - On failure.\n* [`Error`] - Otherwise."
+        );
+    }
+
+    #[test]
+    fn section_order() {
+        let input = "@brief Frees.\n@see alloc()\n@throws Error On failure.\n@returns Nothing.\n\nDetails.\n@param a The value.";
+        assert_eq!(
+            rustdoc(input.into()).unwrap(),
+            "Frees.\n\nDetails.\n\n# Arguments\n\n* `a` - The value.\n\n# Returns\n\nNothing.\n\n# Throws\n\n* [`Error`] - On failure.\n\n# See also\n\n[`alloc()`](alloc)"
+        );
+
+        let options = Options {
+            section_order: vec![Section::Returns, Section::Arguments],
+            ..Default::default()
+        };
+        let result = rustdoc_with_options(input.into(), &options).unwrap();
+        assert_eq!(
+            result,
+            "Frees.\n\n# Returns\n\nNothing.\n\n# Arguments\n\n* `a` - The value.\n\nDetails.\n\n# Throws\n\n* [`Error`] - On failure.\n\n# See also\n\n[`alloc()`](alloc)"
+        );
+    }
+
+    #[test]
+    fn pre_as_safety() {
+        let options = Options {
+            pre_as_safety: true,
+            ..Default::default()
+        };
+
+        let result = rustdoc_with_options(
+            "@brief Frees the example.\n@pre The example must be valid.\n@pre The example must not be used again.".into(),
+            &options,
+        );
+        assert_eq!(
+            result.unwrap(),
+            "Frees the example.\n\n# Safety\n\nThe example must be valid.\nThe example must not be used again."
+        );
+    }
+
+    #[test]
+    fn alerts() {
+        test_rustdoc!("@attention Check it.", "> **Attention:** Check it.");
+
+        let options = Options {
+            admonition_style: AdmonitionStyle::Alert,
+            ..Default::default()
+        };
+        let result = rustdoc_with_options(
+            "@brief Example\n@note A note\non two lines.\n@note Another.\n@attention Careful.\n@important Once.\n@remark Aside.".into(),
+            &options,
+        );
+        assert_eq!(
+            result.unwrap(),
+            "Example\n\n> [!NOTE]\n> A note\n> on two lines.\n\n> [!NOTE]\n> Another.\n\n> [!WARNING]\n> Careful.\n\n> [!IMPORTANT]\n> Once.\n\n> Aside."
+        );
+    }
+
+    #[test]
+    fn admonition_styles() {
+        let input = "@brief Example\n\nText.\n@note A note\non two lines.\n@deprecated Use y.\n@since 1.2\n@remark Aside.";
+        let render = |admonition_style| {
+            let options = Options {
+                admonition_style,
+                ..Default::default()
+            };
+            rustdoc_with_options(input.into(), &options).unwrap()
+        };
+
+        assert_eq!(
+            render(AdmonitionStyle::Label),
+            "Example\n\nText.\n\n**Note:** A note\non two lines.\n\n**Deprecated:** Use y.\n\n**Available since:** 1.2\n\n**Remark:** Aside."
+        );
+        assert_eq!(
+            render(AdmonitionStyle::Alert),
+            "Example\n\nText.\n> [!NOTE]\n> A note\n> on two lines.\n\n> [!CAUTION]\n> **Deprecated** Use y.\n\n> Available since: 1.2\n\n> Aside."
+        );
+        assert_eq!(
+            render(AdmonitionStyle::Template("> _{label}_ | ".into())),
+            "Example\n\nText.\n> _Note_ | A note\n> on two lines.\n\n> _Deprecated_ | Use y.\n\n> _Available since_ | 1.2\n\n> _Remark_ | Aside."
+        );
+    }
+
+    #[test]
+    fn multiline_quotes() {
+        test_rustdoc!(
+            "@note This is a note\nthat spans @b multiple lines.\n\nThis isn't part of it.\n@warning So is\nthis warning.\n@param example An example.",
+            "> **Note:** This is a note\n> that spans **multiple** lines.\n\nThis isn't part of it.\n> **Warning:** So is\n> this warning.\n\n# Arguments\n\n* `example` - An example."
+        );
+    }
+
     #[test]
     fn since() {
         test_rustdoc!(
@@ -359,9 +2650,214 @@ mod test {
         );
     }
 
+    #[test]
+    fn email_addresses() {
+        test_rustdoc!(
+            "@brief Contact support@example.com for @b help.",
+            "Contact support@example.com for **help**."
+        );
+    }
+
+    #[test]
+    fn nested_groups() {
+        test_rustdoc!(
+            "@{\n* @name Memory\n* @{\n* @name Allocation\n* @}\n* @name Mapping\n@}\nOutside.",
+            "# Memory\n## Allocation\nMapping\nOutside."
+        );
+    }
+
+    #[test]
+    fn group_decorations() {
+        test_rustdoc!(
+            "@{\n* @name Memory\n* Allocates **raw** memory.\n*\n* Frees a*b.\n@}",
+            "# Memory\nAllocates **raw** memory.\n\nFrees a*b."
+        );
+    }
+
     #[test]
     fn can_parse_example() {
         let example = include_str!("../tests/assets/example-bindgen.rs");
         println!("{}", rustdoc(example.into()).unwrap());
     }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn tracing_events() {
+        use std::fmt::Debug;
+        use std::sync::{Arc, Mutex};
+        use tracing::field::{Field, Visit};
+        use tracing::span::{Attributes, Id, Record};
+        use tracing::{Event, Metadata, Subscriber};
+
+        /// Collects the messages of the events.
+        #[derive(Clone, Default)]
+        struct Messages(Arc<Mutex<Vec<String>>>);
+
+        impl Visit for Messages {
+            fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+                if field.name() == "message" {
+                    self.0.lock().unwrap().push(format!("{value:?}"));
+                }
+            }
+        }
+
+        impl Subscriber for Messages {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+            fn new_span(&self, _: &Attributes<'_>) -> Id {
+                Id::from_u64(1)
+            }
+            fn record(&self, _: &Id, _: &Record<'_>) {}
+            fn record_follows_from(&self, _: &Id, _: &Id) {}
+            fn event(&self, event: &Event<'_>) {
+                event.record(&mut self.clone());
+            }
+            fn enter(&self, _: &Id) {}
+            fn exit(&self, _: &Id) {}
+        }
+
+        let messages = Messages::default();
+        tracing::subscriber::with_default(messages.clone(), || {
+            rustdoc("@brief Example".into()).unwrap();
+        });
+
+        assert_eq!(
+            *messages.0.lock().unwrap(),
+            [
+                "lexed the input",
+                "parsed the tokens",
+                "translated a tag",
+                "generated text",
+                "generated the Rustdoc",
+            ]
+        );
+    }
+
+    #[cfg(feature = "emoji")]
+    #[test]
+    fn batch() {
+        let inputs = vec![
+            (1, "@brief First"),
+            (2, "Unknown @emoji :nope: and @c"),
+            (3, "@param a"),
+        ];
+        let results = rustdoc_batch(inputs, &Options::default());
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].rustdoc, Ok("First".into()));
+        assert!(results[0].diagnostics.is_empty());
+
+        assert_eq!(results[1].id, 2);
+        assert!(matches!(
+            results[1].rustdoc,
+            Err(ParseError::MissingParameter { .. })
+        ));
+        let severities = results[1]
+            .diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.severity)
+            .collect::<Vec<_>>();
+        assert_eq!(severities, [Severity::Warning, Severity::Error]);
+
+        assert_eq!(results[2].rustdoc, Ok("# Arguments\n\n* `a`".into()));
+        assert_eq!(results[2].diagnostics[0].severity, Severity::Warning);
+    }
+
+    #[cfg(feature = "emoji")]
+    #[test]
+    fn partial_output() {
+        let input = "@brief :nope: @emoji :nope:\n@thisdoesntexist".to_string();
+        let (rustdoc, diagnostics) = rustdoc_partial(input.clone(), &Options::default());
+        assert_eq!(rustdoc, ":nope: :nope:");
+        assert!(matches!(
+            &diagnostics[..],
+            [Diagnostic {
+                severity: Severity::Warning,
+                error: ParseError::UnknownEmoji { line: 1, .. },
+            }]
+        ));
+
+        let options = Options {
+            strict: true,
+            ..Default::default()
+        };
+        let (rustdoc, diagnostics) = rustdoc_partial(input, &options);
+        assert_eq!(rustdoc, ":nope: @emoji :nope:\n@thisdoesntexist");
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|diagnostic| (diagnostic.severity, diagnostic.error.code()))
+                .collect::<Vec<_>>(),
+            [(Severity::Error, "E005"), (Severity::Error, "E003")]
+        );
+    }
+
+    #[test]
+    fn limits() {
+        let options = |limits| Options {
+            limits,
+            ..Default::default()
+        };
+
+        let result = rustdoc_with_options(
+            "@brief Too long".into(),
+            &options(Limits {
+                max_input_len: Some(8),
+                ..Default::default()
+            }),
+        );
+        assert!(matches!(
+            result,
+            Err(ParseError::LimitExceeded {
+                limit: Limit::InputLength,
+                max: 8,
+            })
+        ));
+
+        let deep = options(Limits {
+            max_group_depth: Some(1),
+            ..Default::default()
+        });
+        assert!(rustdoc_with_options("@{ A @} @{ B @}".into(), &deep).is_ok());
+        let (rustdoc, warnings) = rustdoc_lossy("@{ A @{ B @} @}".into(), &deep);
+        assert_eq!(rustdoc, "");
+        assert_eq!(
+            warnings[0].to_string(),
+            "the comment nests more groups than the limit of 1"
+        );
+
+        let result = rustdoc_with_options(
+            "One two three".into(),
+            &options(Limits {
+                max_tokens: Some(4),
+                ..Default::default()
+            }),
+        );
+        assert_eq!(result.unwrap_err().code(), "E010");
+    }
+
+    #[test]
+    fn converter() {
+        let mut converter = Converter::new(Options {
+            strict: true,
+            ..Default::default()
+        });
+
+        let inputs = [
+            "@brief A\n@param a The https://example.com value.",
+            "@nope",
+            "@c b",
+        ];
+        let results = inputs.map(|input| converter.convert(input).map(String::from).ok());
+        assert_eq!(
+            results,
+            [
+                Some(rustdoc_with_options(inputs[0].into(), converter.options()).unwrap()),
+                None,
+                Some("`b`".into())
+            ]
+        );
+        assert_eq!(converter.stats().tags["param"], 1);
+    }
 }
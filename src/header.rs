@@ -0,0 +1,612 @@
+//! Scanning of C and C++ headers for their Doxygen comments, without running Doxygen.
+
+use std::mem;
+
+use crate::comment::{convert_with_options, Change};
+use crate::options::Options;
+use crate::tag::Tag;
+
+/// The words of a declaration that can't be its name.
+const KEYWORDS: &[&str] = &[
+    "bool",
+    "char",
+    "class",
+    "const",
+    "constexpr",
+    "double",
+    "enum",
+    "explicit",
+    "extern",
+    "float",
+    "friend",
+    "inline",
+    "int",
+    "long",
+    "mutable",
+    "namespace",
+    "private",
+    "protected",
+    "public",
+    "register",
+    "restrict",
+    "short",
+    "signed",
+    "static",
+    "struct",
+    "template",
+    "typedef",
+    "typename",
+    "union",
+    "unsigned",
+    "virtual",
+    "void",
+    "volatile",
+];
+
+/// Extracts the Doxygen comments of a C or C++ header, with the names of the declarations they
+/// document, like `("open", "@brief Opens a file.")`.
+///
+/// The comments before a declaration (`/** */`, `/*! */`, `///` and `//!`) document the
+/// declaration following them, and the trailing ones (`/**< */` and `///<`) the declaration
+/// before them. The comments naming what they document with a structural command (like `@def
+/// NAME` or `@file`) are given that name, which is empty for a `@file` without one. The comment
+/// decorations are removed, so the comments can be translated with
+/// [`crate::generator::rustdoc`].
+///
+/// The declarations are found with a light scan of the source rather than a full parse, so the
+/// declarations hidden behind macros may not be named properly.
+///
+/// # Examples
+///
+/// ```
+/// use doxygen_rs::header::scan;
+///
+/// let header = "
+/// /**
+///  * @brief Opens a file.
+///  */
+/// int open(const char *path, enum mode mode);
+///
+/// enum mode {
+///     READ,  ///< Read only.
+///     WRITE, ///< Write only.
+/// };";
+///
+/// let blocks = scan(header);
+/// assert_eq!(blocks[0], ("open".into(), "@brief Opens a file.".into()));
+/// assert_eq!(blocks[1], ("READ".into(), "Read only.".into()));
+/// assert_eq!(blocks[2], ("WRITE".into(), "Write only.".into()));
+/// ```
+pub fn scan(source: &str) -> Vec<(String, String)> {
+    let mut scanner = Scanner::default();
+    let mut index = 0;
+    let mut line_start = true;
+
+    while let Some(c) = source[index..].chars().next() {
+        let rest = &source[index..];
+
+        let length = if let Some((comment, trailing, length)) = doc_comment(rest) {
+            scanner.comment(comment, trailing);
+            length
+        } else if rest.starts_with("//") {
+            rest.find('\n').unwrap_or(rest.len())
+        } else if let Some(comment) = rest.strip_prefix("/*") {
+            comment.find("*/").map_or(rest.len(), |end| end + 4)
+        } else if line_start && c == '#' {
+            let length = directive_length(rest);
+            scanner.directive(&rest[..length]);
+            length
+        } else if c == '"' || c == '\'' {
+            literal_length(rest, c)
+        } else {
+            scanner.code(c);
+            c.len_utf8()
+        };
+
+        line_start = c == '\n' || (line_start && c.is_whitespace());
+        index += length;
+    }
+
+    scanner.blocks
+}
+
+/// The state of [`scan`].
+#[derive(Default)]
+struct Scanner {
+    blocks: Vec<(String, String)>,
+    /// The comment waiting for the declaration following it.
+    pending: Option<String>,
+    /// The declaration being read, since the last `;`, `,`, `{` or `}`.
+    declaration: String,
+    /// The name of the last complete declaration, for the trailing comments.
+    previous: Option<String>,
+    /// The comments of the unnamed types (like in `typedef struct { ... } name;`) opened by the
+    /// enclosing braces, which are named after their closing brace.
+    braces: Vec<Option<String>>,
+    /// The depth of the parentheses, in which the commas separate parameters.
+    parentheses: usize,
+}
+
+impl Scanner {
+    fn comment(&mut self, comment: String, trailing: bool) {
+        if let Some(symbol) = structural_symbol(&comment) {
+            self.blocks.push((symbol.into(), comment));
+        } else if trailing {
+            if let Some(symbol) = symbol(&self.declaration).or(self.previous.as_deref()) {
+                self.blocks.push((symbol.into(), comment));
+            }
+        } else if let Some(pending) = &mut self.pending {
+            pending.push('\n');
+            pending.push_str(&comment);
+        } else {
+            self.pending = Some(comment);
+        }
+    }
+
+    fn code(&mut self, c: char) {
+        match c {
+            ';' | '{' | '}' => return self.end_declaration(c),
+            ',' if self.parentheses == 0 => return self.end_declaration(c),
+            '(' => self.parentheses += 1,
+            ')' => self.parentheses = self.parentheses.saturating_sub(1),
+            _ => {}
+        }
+
+        self.declaration.push(c);
+    }
+
+    fn end_declaration(&mut self, end: char) {
+        let declaration = mem::take(&mut self.declaration);
+        let symbol = symbol(&declaration);
+        self.parentheses = 0;
+
+        if let (Some(symbol), Some(comment)) = (symbol, &self.pending) {
+            self.blocks.push((symbol.into(), comment.clone()));
+            self.pending = None;
+        }
+
+        match end {
+            '{' if symbol.is_none() => self.braces.push(self.pending.take()),
+            '{' => self.braces.push(None),
+            '}' => {
+                if let Some(Some(comment)) = self.braces.pop() {
+                    self.pending = Some(comment);
+                }
+            }
+            _ => {}
+        }
+
+        self.previous = symbol.map(String::from);
+    }
+
+    fn directive(&mut self, directive: &str) {
+        let Some(definition) = directive[1..].trim_start().strip_prefix("define") else {
+            return;
+        };
+
+        let name = definition
+            .trim_start()
+            .split(|c: char| !is_identifier(c))
+            .next()
+            .filter(|name| !name.is_empty());
+        if let (Some(name), Some(comment)) = (name, self.pending.take()) {
+            self.blocks.push((name.into(), comment));
+        }
+
+        self.previous = name.map(String::from);
+    }
+}
+
+/// Returns the text of the Doxygen comment at the start of the text (if any), whether it's a
+/// trailing comment, and the length of the comment. The consecutive lines of `///` and `//!`
+/// comments are a single comment.
+fn doc_comment(text: &str) -> Option<(String, bool, usize)> {
+    if let Some(body) = text
+        .strip_prefix("/**")
+        .or_else(|| text.strip_prefix("/*!"))
+    {
+        // Banners like `/*****` and empty comments like `/**/` aren't Doxygen
+        if body.starts_with(['*', '/']) {
+            return None;
+        }
+
+        let end = body.find("*/");
+        let length = end.map_or(text.len(), |end| end + 5);
+        let body = &body[..end.unwrap_or(body.len())];
+        let (body, trailing) = match body.strip_prefix('<') {
+            Some(body) => (body, true),
+            None => (body, false),
+        };
+
+        let lines = body.lines().map(|line| {
+            let trimmed = line.trim_start();
+            match trimmed.strip_prefix('*') {
+                Some(line) => line.strip_prefix(' ').unwrap_or(line),
+                None => trimmed,
+            }
+        });
+
+        return Some((join_lines(lines), trailing, length));
+    }
+
+    let marker = ["///", "//!"]
+        .into_iter()
+        .find(|marker| text.starts_with(marker))?;
+    if text[marker.len()..].starts_with('/') {
+        return None;
+    }
+
+    let trailing = text[marker.len()..].starts_with('<');
+    let mut lines = vec![];
+    let mut start = 0;
+    loop {
+        let end = text[start..]
+            .find('\n')
+            .map_or(text.len(), |end| start + end);
+        let line = &text[start + marker.len()..end];
+        let line = match trailing {
+            true => line.strip_prefix('<').unwrap_or(line),
+            false => line,
+        };
+        lines.push(line.strip_prefix(' ').unwrap_or(line));
+
+        // A trailing comment only documents the declaration of its line
+        let next = text[end..].trim_start_matches([' ', '\t', '\r']);
+        let next = next
+            .strip_prefix('\n')
+            .map(|next| next.trim_start_matches([' ', '\t']));
+        match next {
+            Some(next) if !trailing && next.starts_with(marker) && !next.starts_with("////") => {
+                if next[marker.len()..].starts_with('<') {
+                    return Some((join_lines(lines.into_iter()), trailing, end));
+                }
+                start = text.len() - next.len();
+            }
+            _ => return Some((join_lines(lines.into_iter()), trailing, end)),
+        }
+    }
+}
+
+/// Joins the lines of a comment, leaving out the blank lines around it.
+fn join_lines<'a>(lines: impl Iterator<Item = &'a str>) -> String {
+    let lines = lines.map(str::trim_end).collect::<Vec<_>>();
+    let start = lines.iter().position(|line| !line.is_empty());
+    let end = lines.iter().rposition(|line| !line.is_empty());
+
+    match (start, end) {
+        (Some(start), Some(end)) => lines[start..=end].join("\n"),
+        _ => String::new(),
+    }
+}
+
+/// Returns the length of the preprocessor directive at the start of the text, up to the end of
+/// its last line (following the `\` continuations) or the start of a comment.
+fn directive_length(text: &str) -> usize {
+    let mut chars = text.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next_if(|(_, c)| *c == '\r');
+                chars.next_if(|(_, c)| *c == '\n');
+            }
+            '\n' => return index,
+            '/' if matches!(chars.peek(), Some((_, '/' | '*'))) => return index,
+            _ => {}
+        }
+    }
+
+    text.len()
+}
+
+/// Returns the length of the string or character literal at the start of the text.
+fn literal_length(text: &str, quote: char) -> usize {
+    let mut chars = text.char_indices().skip(1);
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '\n' => return index,
+            c if c == quote => return index + 1,
+            _ => {}
+        }
+    }
+
+    text.len()
+}
+
+/// Returns the name of a declaration, like `open` for `int open(const char *path)`.
+fn symbol(declaration: &str) -> Option<&str> {
+    let mut declaration = declaration;
+    if let Some(open) = declaration.find('(') {
+        let parameters = declaration[open + 1..].trim_start();
+        declaration = match parameters.strip_prefix(['*', '^', '&']) {
+            // Function pointers, like `void (*callback)(int)`
+            Some(pointer) => pointer,
+            None => &declaration[..open],
+        };
+    }
+
+    // Leave out the values, array sizes and bit fields, but not the `::` of the C++ paths
+    let bytes = declaration.as_bytes();
+    let end = (0..bytes.len())
+        .find(|&index| match bytes[index] {
+            b'=' | b'[' | b')' => true,
+            b':' => bytes.get(index + 1) != Some(&b':') && (index == 0 || bytes[index - 1] != b':'),
+            _ => false,
+        })
+        .unwrap_or(bytes.len());
+
+    declaration[..end]
+        .split(|c: char| !is_identifier(c))
+        .rfind(|word| {
+            !word.is_empty()
+                && !word.starts_with(|c: char| c.is_ascii_digit())
+                && !KEYWORDS.contains(word)
+        })
+}
+
+/// A difference between the documentation of two versions of a header, found by [`compare`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum HeaderChange {
+    /// A symbol documented only by the new version.
+    Added(String),
+    /// A symbol documented only by the old version.
+    Removed(String),
+    /// A symbol whose documentation changed.
+    Changed {
+        symbol: String,
+        changes: Vec<Change>,
+    },
+}
+
+/// Compares the documentation of two versions of a header, like before and after an update of a
+/// library, and returns the symbols whose documentation was added, removed or changed, in the
+/// order of the headers.
+///
+/// The comments are found with [`scan`] and translated with the given options, so only the
+/// semantic differences are reported (like an added parameter, but not a rewrapped paragraph).
+/// When a version of a comment can't be translated, its raw text is compared instead, as a
+/// `comment` section.
+///
+/// # Examples
+///
+/// ```
+/// use doxygen_rs::comment::Change;
+/// use doxygen_rs::header::{compare, HeaderChange};
+/// use doxygen_rs::options::Options;
+///
+/// let old = "/** @brief Opens a file. */\nint open(const char *path);";
+/// let new = "/** @brief Opens a file.\n * @param flags The flags. */\nint open(const char *path, int flags);";
+///
+/// assert_eq!(
+///     compare(old, new, &Options::default()),
+///     vec![HeaderChange::Changed {
+///         symbol: "open".into(),
+///         changes: vec![Change::AddedParam("flags".into())],
+///     }]
+/// );
+/// ```
+pub fn compare(old_source: &str, new_source: &str, options: &Options) -> Vec<HeaderChange> {
+    let (old, new) = (scan(old_source), scan(new_source));
+    let mut changes = vec![];
+
+    for (symbol, old_comment) in &old {
+        let Some((_, new_comment)) = new.iter().find(|(name, _)| name == symbol) else {
+            changes.push(HeaderChange::Removed(symbol.clone()));
+            continue;
+        };
+
+        let comment_changes = match (
+            convert_with_options(old_comment.clone(), options),
+            convert_with_options(new_comment.clone(), options),
+        ) {
+            (Ok(old), Ok(new)) => old.changes(&new),
+            _ if old_comment != new_comment => vec![Change::ChangedSection {
+                section: "comment",
+                old: old_comment.clone(),
+                new: new_comment.clone(),
+            }],
+            _ => vec![],
+        };
+
+        if !comment_changes.is_empty() {
+            changes.push(HeaderChange::Changed {
+                symbol: symbol.clone(),
+                changes: comment_changes,
+            });
+        }
+    }
+
+    changes.extend(
+        new.iter()
+            .filter(|(symbol, _)| !old.iter().any(|(name, _)| name == symbol))
+            .map(|(symbol, _)| HeaderChange::Added(symbol.clone())),
+    );
+
+    changes
+}
+
+/// Returns the name given by the structural command of a comment (if any), like `NAME` for
+/// `@def NAME`.
+fn structural_symbol(comment: &str) -> Option<&str> {
+    comment.lines().find_map(|line| {
+        let mut words = line.split_whitespace();
+        words.by_ref().find(|word| {
+            word.strip_prefix(['@', '\\'])
+                .is_some_and(|name| Tag::from(name).is_structural())
+        })?;
+
+        Some(words.next().unwrap_or_default())
+    })
+}
+
+fn is_identifier(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn pairs(blocks: &[(&str, &str)]) -> Vec<(String, String)> {
+        blocks
+            .iter()
+            .map(|(symbol, comment)| (symbol.to_string(), comment.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn declarations() {
+        let header = r#"
+#include <stdio.h>
+
+/**
+ * @brief Opens a file.
+ *
+ * @param path The path.
+ */
+FILE *open(const char *path, int flags);
+
+/// The callback.
+/// Called on close.
+typedef void (*close_cb)(FILE *file);
+
+/*! The size. */
+extern const unsigned long buffer_size[4];
+
+/* Not documented */
+int undocumented(void);
+"#;
+
+        assert_eq!(
+            scan(header),
+            pairs(&[
+                ("open", "@brief Opens a file.\n\n@param path The path."),
+                ("close_cb", "The callback.\nCalled on close."),
+                ("buffer_size", "The size."),
+            ])
+        );
+    }
+
+    #[test]
+    fn members() {
+        let header = "
+/// The modes.
+typedef enum {
+    /// Read only.
+    READ = 1,
+    WRITE = 2, /**< Write only. */
+    APPEND ///< Append.
+} mode_t;
+
+/** A point. */
+struct point {
+    int x; ///< The x.
+    int y : 4; //!< The y.
+};";
+
+        assert_eq!(
+            scan(header),
+            pairs(&[
+                ("READ", "Read only."),
+                ("WRITE", "Write only."),
+                ("APPEND", "Append."),
+                ("mode_t", "The modes."),
+                ("point", "A point."),
+                ("x", "The x."),
+                ("y", "The y."),
+            ])
+        );
+    }
+
+    #[test]
+    fn macros() {
+        let header = "
+/** The maximum. */
+#define MAX(a, b) \\
+    ((a) > (b) ? (a) : (b))
+
+#define VERSION 3 ///< The version.
+
+/**
+ * @file
+ * @brief The file.
+ */
+
+/** @def ALIAS
+ * An alias.
+ */
+const char *name = \"/** not a comment */\";";
+
+        assert_eq!(
+            scan(header),
+            pairs(&[
+                ("MAX", "The maximum."),
+                ("VERSION", "The version."),
+                ("", "@file\n@brief The file."),
+                ("ALIAS", "@def ALIAS\nAn alias."),
+            ])
+        );
+    }
+
+    #[test]
+    fn compares_versions() {
+        let old = "
+/** @brief Opens a file.
+ *  @param path The path. */
+int open(const char *path);
+
+/// Reads a file.
+int read(int fd);
+
+/// Closes a file.
+int close(int fd);
+";
+        let new = "
+/** @brief Opens a file.
+ *  @param[in] path The path. */
+int open(const char *path);
+
+/// Reads a
+/// file.
+/// @deprecated Use `read_all`.
+int read(int fd);
+
+/// Reads a whole file.
+int read_all(int fd);
+";
+
+        assert_eq!(compare(old, old, &Options::default()), vec![]);
+        assert_eq!(
+            compare(old, new, &Options::default()),
+            vec![
+                HeaderChange::Changed {
+                    symbol: "open".into(),
+                    changes: vec![Change::ChangedParam {
+                        old: crate::comment::Param {
+                            name: "path".into(),
+                            direction: None,
+                            description: "The path.".into(),
+                        },
+                        new: crate::comment::Param {
+                            name: "path".into(),
+                            direction: Some(crate::comment::Direction::In),
+                            description: "The path.".into(),
+                        },
+                    }],
+                },
+                HeaderChange::Changed {
+                    symbol: "read".into(),
+                    changes: vec![Change::ChangedSection {
+                        section: "deprecated",
+                        old: "".into(),
+                        new: "Use `read_all`.".into(),
+                    }],
+                },
+                HeaderChange::Removed("close".into()),
+                HeaderChange::Added("read_all".into()),
+            ]
+        );
+    }
+}
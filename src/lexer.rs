@@ -1,63 +1,370 @@
-#[derive(Debug, Clone, Eq, PartialEq)]
-pub(crate) enum LexItem {
-    At(String),
+//! The tokens of the Doxygen, before they are parsed into tags and text.
+
+use std::collections::VecDeque;
+use std::ops::Range;
+use std::str::CharIndices;
+
+/// A token of the Doxygen, borrowing its text from the input.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum LexItem<'a> {
+    /// The prefix of a command, like `@`, `\` or `\\`.
+    At(&'a str),
+    /// The brace of a group, like the `{` of `@{`.
     Paren(char),
-    Word(String),
+    /// A word, up to the next space.
+    Word(&'a str),
+    /// A URL split out of a word, like `https://example.com` in `(https://example.com)`.
+    Url(&'a str),
+    /// The spaces between the words, as one token (except in code blocks and in the indentation of
+    /// the list items, where every space is kept).
     Space,
+    /// The end of a line.
     NewLine,
 }
 
-pub(crate) fn lex(input: String) -> Vec<LexItem> {
-    let mut result = vec![];
+#[cfg(test)]
+pub(crate) fn lex(input: &str) -> Vec<LexItem<'_>> {
+    Lexer::new(input).collect()
+}
 
-    for c in input.chars() {
-        match c {
-            '@' => {
-                result.push(LexItem::At(c.into()));
+/// Lexes the Doxygen lazily, one token at a time, so the lexing can stop early (like once there
+/// are too many tokens).
+///
+/// # Examples
+///
+/// ```
+/// use doxygen_rs::lexer::{LexItem, Lexer};
+///
+/// let tokens = Lexer::new("@brief Opens it").collect::<Vec<_>>();
+///
+/// assert_eq!(
+///     tokens,
+///     [
+///         LexItem::At("@"),
+///         LexItem::Word("brief"),
+///         LexItem::Space,
+///         LexItem::Word("Opens"),
+///         LexItem::Space,
+///         LexItem::Word("it"),
+///     ]
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct Lexer<'a> {
+    input: &'a str,
+    chars: CharIndices<'a>,
+    /// The start of the code or verbatim region the input is in and the command ending it.
+    /// Commands aren't interpreted, whitespace is kept and the URLs are left as they are in the
+    /// regions
+    region: Option<(usize, &'static str)>,
+    /// The last token, which is only complete once the next one starts (like a word, which
+    /// continues up to the next space)
+    last: Option<LexItem<'a>>,
+    /// The start of the last token in the input
+    last_start: usize,
+    /// The tokens split from the last word, like the URLs in it
+    pending: VecDeque<LexItem<'a>>,
+    /// Whether the last token is an `@`, to tell the commands apart from the other words
+    after_at: bool,
+    /// Whether only spaces follow the start of the current line
+    at_line_start: bool,
+    /// Whether the spaces starting the current line indent a list item, once found
+    list_indentation: Option<bool>,
+}
+
+impl<'a> Lexer<'a> {
+    /// Creates a lexer of the input.
+    pub fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            chars: input.char_indices(),
+            region: None,
+            last: None,
+            last_start: 0,
+            pending: VecDeque::new(),
+            after_at: false,
+            at_line_start: true,
+            list_indentation: None,
+        }
+    }
+
+    /// Lexes the next token, before the URLs are split out of the words. Returns the token with
+    /// whether it's part of a code or verbatim region.
+    fn next_token(&mut self) -> Option<(LexItem<'a>, bool)> {
+        for (index, c) in self.chars.by_ref() {
+            // The text from the start of the last token up to (and including) the character, to
+            // extend the last token with it
+            let extended = &self.input[self.last_start..index + c.len_utf8()];
+            let text = &self.input[index..index + c.len_utf8()];
+            let in_region = self.region.is_some_and(|(start, _)| index >= start);
+
+            match c {
+                '\n' => {
+                    self.at_line_start = true;
+                    self.list_indentation = None;
+                }
+                ' ' => {}
+                _ => self.at_line_start = false,
             }
-            '\\' => {
-                if let Some(value) = result.last_mut() {
-                    match value {
-                        LexItem::At(v) => {
-                            if v == "\\" {
-                                *v += "\\"
-                            } else {
-                                result.push(LexItem::At(c.into()))
-                            }
-                        }
-                        _ => result.push(LexItem::At(c.into())),
+
+            if let Some((_, end)) = self.region.filter(|_| in_region && matches!(c, '@' | '\\')) {
+                if starts_with_command(&self.input[index + 1..], end) {
+                    self.region = None;
+                    let last = self.last.replace(LexItem::At(text));
+                    self.last_start = index;
+                    match last {
+                        Some(token) => return Some((token, true)),
+                        None => continue,
                     }
-                } else {
-                    result.push(LexItem::At(c.into()));
                 }
             }
-            '{' | '}' => {
-                result.push(LexItem::Paren(c));
-            }
-            ' ' => {
-                if let Some(v) = result.last_mut() {
-                    if !matches!(v, LexItem::Space) {
-                        result.push(LexItem::Space);
+
+            // The spaces indenting a list item (like the ones of `  - item`) are kept, to keep the
+            // nesting of the lists. The rest of the line is only read once, so that long runs of
+            // spaces are lexed in linear time
+            let list_indentation = c == ' '
+                && self.at_line_start
+                && *self.list_indentation.get_or_insert_with(|| {
+                    starts_with_list_item(self.input[index..].trim_start_matches(' '))
+                });
+            let token = match c {
+                '@' if !in_region => {
+                    // An `@` right after a word (like in `support@example.com`) isn't a command
+                    if let Some(LexItem::Word(word)) = &mut self.last {
+                        if word.ends_with(|c: char| {
+                            c.is_alphanumeric() || matches!(c, '.' | '_' | '-' | '+')
+                        }) {
+                            *word = extended;
+                            continue;
+                        }
                     }
+
+                    LexItem::At(text)
                 }
+                '\\' if !in_region => match &mut self.last {
+                    Some(LexItem::At(v)) if *v == "\\" => {
+                        *v = extended;
+                        continue;
+                    }
+                    // A backslash right after a word (like in `C:\\code`) isn't a command
+                    Some(LexItem::Word(word))
+                        if word.ends_with(|c: char| {
+                            c.is_alphanumeric() || matches!(c, ':' | '.' | '_' | '-')
+                        }) =>
+                    {
+                        *word = extended;
+                        continue;
+                    }
+                    _ => LexItem::At(text),
+                },
+                '{' | '}' if !in_region => LexItem::Paren(c),
+                ' ' => match &self.last {
+                    Some(LexItem::Space) if !in_region && !list_indentation => continue,
+                    Some(_) => LexItem::Space,
+                    None => continue,
+                },
+                '\n' => LexItem::NewLine,
+                _ => match &mut self.last {
+                    Some(LexItem::Word(v)) => {
+                        *v = extended;
+                        continue;
+                    }
+                    _ => LexItem::Word(text),
+                },
+            };
+
+            // Only the commands start a region, like `@code` but not `dev@code.example.com`
+            if matches!(token, LexItem::At(_)) && self.region.is_none() {
+                self.region = find_region(&self.input[index + 1..])
+                    .map(|(length, end)| (index + 1 + length, end));
+            }
+
+            let in_region = self.region.is_some();
+            self.last_start = index;
+            if let Some(token) = self.last.replace(token) {
+                return Some((token, in_region));
+            }
+        }
+
+        let in_region = self.region.is_some();
+        self.last.take().map(|token| (token, in_region))
+    }
+
+    /// Splits the URLs out of the word into the pending tokens, leaving the punctuation around
+    /// them (like the period ending a sentence) in separate words.
+    fn split_urls(&mut self, mut word: &'a str) {
+        while let Some((start, end)) = find_url(word) {
+            if start > 0 {
+                self.pending.push_back(LexItem::Word(&word[..start]));
             }
-            '\n' => {
-                result.push(LexItem::NewLine);
+
+            self.pending.push_back(LexItem::Url(&word[start..end]));
+            word = &word[end..];
+        }
+
+        if !word.is_empty() {
+            self.pending.push_back(LexItem::Word(word));
+        }
+    }
+}
+
+impl<'a> Iterator for Lexer<'a> {
+    type Item = LexItem<'a>;
+
+    fn next(&mut self) -> Option<LexItem<'a>> {
+        loop {
+            if let Some(token) = self.pending.pop_front() {
+                return Some(token);
             }
-            _ => {
-                if let Some(v) = result.last_mut() {
-                    match v {
-                        LexItem::Word(v) => *v += &c.to_string(),
-                        _ => result.push(LexItem::Word(String::from(c))),
+
+            let (token, in_region) = self.next_token()?;
+            let after_at = std::mem::replace(&mut self.after_at, matches!(token, LexItem::At(_)));
+
+            match token {
+                LexItem::Word(word) => {
+                    if in_region {
+                        return Some(LexItem::Word(word));
                     }
-                } else {
-                    result.push(LexItem::Word(String::from(c)))
+
+                    // A `%` before a word keeps Doxygen from linking it, like in `%Foo`
+                    let word = match word.strip_prefix('%') {
+                        Some(rest)
+                            if !after_at
+                                && rest.starts_with(|c: char| c.is_alphabetic() || c == '_') =>
+                        {
+                            rest
+                        }
+                        _ => word,
+                    };
+
+                    self.split_urls(word);
                 }
+                token => return Some(token),
             }
         }
     }
+}
+
+/// Returns the byte range of the text of a token (like a [`LexItem::Word`]) in the input it was
+/// lexed from.
+pub(crate) fn span_of(input: &str, text: &str) -> Range<usize> {
+    let start = text.as_ptr() as usize - input.as_ptr() as usize;
+    debug_assert!(
+        start + text.len() <= input.len(),
+        "the text isn't part of the input"
+    );
+
+    start..start + text.len()
+}
+
+/// Whether the text starts with the marker of a list item, like `- item` or `1. item`. The asterisk
+/// isn't considered a list marker, as it's usually the decoration of the comment.
+fn starts_with_list_item(rest: &str) -> bool {
+    let digits = rest.chars().take_while(char::is_ascii_digit).count();
+
+    ["- ", "+ ", "-# "]
+        .iter()
+        .any(|marker| rest.starts_with(marker))
+        || (digits > 0 && (rest[digits..].starts_with(". ") || rest[digits..].starts_with(") ")))
+}
+
+/// Finds the code, verbatim or foreign markup (like DocBook) region started by the command at the
+/// start of the text, like `code{.py}`. Returns the length of the command and the command ending
+/// the region.
+fn find_region(text: &str) -> Option<(usize, &'static str)> {
+    let (name, end) = [
+        ("code", "endcode"),
+        ("verbatim", "endverbatim"),
+        ("docbookonly", "enddocbookonly"),
+    ]
+    .into_iter()
+    .find(|(name, _)| starts_with_command(text, name))?;
+
+    let attributes = &text[name.len()..];
+    let attributes_length = match attributes.strip_prefix('{') {
+        Some(rest) => rest.find('}').map_or(0, |index| index + 2),
+        None => 0,
+    };
 
-    result
+    Some((name.len() + attributes_length, end))
+}
+
+/// Whether the text starts with the given command name, like `code` in `code{.py}`.
+fn starts_with_command(text: &str, name: &str) -> bool {
+    text.strip_prefix(name)
+        .is_some_and(|rest| !rest.starts_with(|c: char| c.is_alphanumeric() || c == '_'))
+}
+
+/// The schemes of the URLs that are turned into links.
+const URL_SCHEMES: [&str; 5] = ["https://", "http://", "ftp://", "mailto:", "www."];
+
+/// Whether the whole word is a URL, like `https://example.com`.
+pub(crate) fn is_url(word: &str) -> bool {
+    find_url(word) == Some((0, word.len()))
+}
+
+/// Finds the start and the end of the first URL in the word, if any.
+fn find_url(word: &str) -> Option<(usize, usize)> {
+    // Searching the schemes is slow compared to the rest of the lexing, and most words can't have
+    // any of them
+    if !word.contains(':') && !word.as_bytes().windows(4).any(|bytes| bytes == b"www.") {
+        return None;
+    }
+
+    let (start, scheme) = URL_SCHEMES
+        .iter()
+        .filter_map(|scheme| word.find(scheme).map(|start| (start, scheme)))
+        .min_by_key(|(start, _)| *start)?;
+
+    // Attributes of HTML tags, like `<a href="https://example.com">`, are kept as they are
+    if word[..start].ends_with(['=', '"', '\'']) {
+        return None;
+    }
+
+    // Only a whole word is a URL without a scheme, like `www.example.com` but not `awww.com`
+    if *scheme == "www." && word[..start].ends_with(char::is_alphanumeric) {
+        return None;
+    }
+
+    let rest = &word[start..];
+    let (_, url, _) =
+        split_punctuation(&rest[..rest.find(['"', '\'', '<', '>']).unwrap_or(rest.len())]);
+
+    if url.len() <= scheme.len() {
+        return None;
+    }
+
+    Some((start, start + url.len()))
+}
+
+/// Splits the punctuation around a word, like the parenthesis and the comma in `(word),`, returning
+/// the leading punctuation, the word and the trailing punctuation. Closing parentheses and brackets
+/// that are part of the word (like in `f(x)`) are kept, and words made only of punctuation are
+/// returned whole.
+pub(crate) fn split_punctuation(word: &str) -> (&str, &str, &str) {
+    let start = word.len() - word.trim_start_matches(['(', '[', '"', '\'']).len();
+    let mut end = word.len();
+
+    loop {
+        let rest = &word[start..end];
+        let is_unbalanced = |open, close| {
+            rest.ends_with(close) && rest.matches(close).count() > rest.matches(open).count()
+        };
+
+        if let Some(trimmed) = rest.strip_suffix(['.', ',', ';', ':', '!', '?', '"', '\'']) {
+            end = start + trimmed.len();
+        } else if is_unbalanced('(', ')') || is_unbalanced('[', ']') {
+            end -= 1;
+        } else {
+            break;
+        }
+    }
+
+    if start >= end {
+        return ("", word, "");
+    }
+
+    (&word[..start], &word[start..end], &word[end..])
 }
 
 #[cfg(test)]
@@ -66,67 +373,287 @@ mod test {
 
     #[test]
     fn basic_notation() {
-        let result = lex("@name Memory Management".into());
+        let result = lex("@name Memory Management");
+        assert_eq!(
+            result,
+            vec![
+                LexItem::At("@"),
+                LexItem::Word("name"),
+                LexItem::Space,
+                LexItem::Word("Memory"),
+                LexItem::Space,
+                LexItem::Word("Management")
+            ]
+        );
+
+        let result = lex("\\name Memory Management");
+        assert_eq!(
+            result,
+            vec![
+                LexItem::At("\\"),
+                LexItem::Word("name"),
+                LexItem::Space,
+                LexItem::Word("Memory"),
+                LexItem::Space,
+                LexItem::Word("Management")
+            ]
+        );
+
+        let result = lex("\\\\name Memory Management");
+        assert_eq!(
+            result,
+            vec![
+                LexItem::At("\\\\"),
+                LexItem::Word("name"),
+                LexItem::Space,
+                LexItem::Word("Memory"),
+                LexItem::Space,
+                LexItem::Word("Management")
+            ]
+        );
+    }
+
+    #[test]
+    fn at_after_word() {
+        let result = lex("support@example.com (@c x)");
+        assert_eq!(
+            result,
+            vec![
+                LexItem::Word("support@example.com"),
+                LexItem::Space,
+                LexItem::Word("("),
+                LexItem::At("@"),
+                LexItem::Word("c"),
+                LexItem::Space,
+                LexItem::Word("x)"),
+            ]
+        );
+    }
+
+    #[test]
+    fn regions_after_words() {
+        let result = lex("dev@code.example.com C:\\code\\proj\n@b x");
         assert_eq!(
             result,
             vec![
-                LexItem::At("@".into()),
-                LexItem::Word("name".into()),
+                LexItem::Word("dev@code.example.com"),
+                LexItem::Space,
+                LexItem::Word("C:\\code\\proj"),
+                LexItem::NewLine,
+                LexItem::At("@"),
+                LexItem::Word("b"),
+                LexItem::Space,
+                LexItem::Word("x"),
+            ]
+        );
+
+        let result = lex("admin@verbatim.io \\ref x");
+        assert_eq!(
+            result,
+            vec![
+                LexItem::Word("admin@verbatim.io"),
+                LexItem::Space,
+                LexItem::At("\\"),
+                LexItem::Word("ref"),
+                LexItem::Space,
+                LexItem::Word("x"),
+            ]
+        );
+    }
+
+    #[test]
+    fn space_runs() {
+        let spaces = " ".repeat(10_000);
+        let input = format!("a{spaces}b\n{spaces}- c\n{spaces}d");
+        let result = lex(&input);
+        assert_eq!(result.len(), 10 + 10_000);
+        assert_eq!(
+            result[..3],
+            [LexItem::Word("a"), LexItem::Space, LexItem::Word("b")]
+        );
+        assert!(result[4..4 + 10_000]
+            .iter()
+            .all(|item| *item == LexItem::Space));
+        assert_eq!(
+            result[4 + 10_000..],
+            [
+                LexItem::Word("-"),
                 LexItem::Space,
-                LexItem::Word("Memory".into()),
+                LexItem::Word("c"),
+                LexItem::NewLine,
                 LexItem::Space,
-                LexItem::Word("Management".into())
+                LexItem::Word("d"),
             ]
         );
+    }
 
-        let result = lex("\\name Memory Management".into());
+    #[test]
+    fn urls() {
+        let result = lex("See https://example.com/docs. (or http://example.com/a_(b))");
         assert_eq!(
             result,
             vec![
-                LexItem::At("\\".into()),
-                LexItem::Word("name".into()),
+                LexItem::Word("See"),
                 LexItem::Space,
-                LexItem::Word("Memory".into()),
+                LexItem::Url("https://example.com/docs"),
+                LexItem::Word("."),
                 LexItem::Space,
-                LexItem::Word("Management".into())
+                LexItem::Word("(or"),
+                LexItem::Space,
+                LexItem::Url("http://example.com/a_(b)"),
+                LexItem::Word(")"),
             ]
         );
 
-        let result = lex("\\\\name Memory Management".into());
+        let result = lex("<a href=\"https://example.com\">");
         assert_eq!(
             result,
             vec![
-                LexItem::At("\\\\".into()),
-                LexItem::Word("name".into()),
+                LexItem::Word("<a"),
                 LexItem::Space,
-                LexItem::Word("Memory".into()),
+                LexItem::Word("href=\"https://example.com\">"),
+            ]
+        );
+    }
+
+    #[test]
+    fn url_schemes() {
+        let result = lex("ftp://example.com mailto:support@example.com (www.example.com) awww.x");
+        assert_eq!(
+            result,
+            vec![
+                LexItem::Url("ftp://example.com"),
                 LexItem::Space,
-                LexItem::Word("Management".into())
+                LexItem::Url("mailto:support@example.com"),
+                LexItem::Space,
+                LexItem::Word("("),
+                LexItem::Url("www.example.com"),
+                LexItem::Word(")"),
+                LexItem::Space,
+                LexItem::Word("awww.x"),
+            ]
+        );
+    }
+
+    #[test]
+    fn autolink_suppression() {
+        assert_eq!(
+            lex("%Foo 100% %1 @code\n%x\n@endcode"),
+            vec![
+                LexItem::Word("Foo"),
+                LexItem::Space,
+                LexItem::Word("100%"),
+                LexItem::Space,
+                LexItem::Word("%1"),
+                LexItem::Space,
+                LexItem::At("@"),
+                LexItem::Word("code"),
+                LexItem::NewLine,
+                LexItem::Word("%x"),
+                LexItem::NewLine,
+                LexItem::At("@"),
+                LexItem::Word("endcode"),
+            ]
+        );
+    }
+
+    #[test]
+    fn punctuation() {
+        assert_eq!(split_punctuation("(word),"), ("(", "word", "),"));
+        assert_eq!(split_punctuation("f(x)."), ("", "f(x)", "."));
+        assert_eq!(split_punctuation("\"a[0]\""), ("\"", "a[0]", "\""));
+        assert_eq!(split_punctuation("..."), ("", "...", ""));
+    }
+
+    #[test]
+    fn code_whitespace() {
+        let result = lex("@code\n  a  b\n@endcode c  d");
+        assert_eq!(
+            result,
+            vec![
+                LexItem::At("@"),
+                LexItem::Word("code"),
+                LexItem::NewLine,
+                LexItem::Space,
+                LexItem::Space,
+                LexItem::Word("a"),
+                LexItem::Space,
+                LexItem::Space,
+                LexItem::Word("b"),
+                LexItem::NewLine,
+                LexItem::At("@"),
+                LexItem::Word("endcode"),
+                LexItem::Space,
+                LexItem::Word("c"),
+                LexItem::Space,
+                LexItem::Word("d"),
+            ]
+        );
+    }
+
+    #[test]
+    fn code_commands() {
+        let result = lex("@code{.c}\n{@param \\n https://example.com}\n@endcode");
+        assert_eq!(
+            result,
+            vec![
+                LexItem::At("@"),
+                LexItem::Word("code"),
+                LexItem::Paren('{'),
+                LexItem::Word(".c"),
+                LexItem::Paren('}'),
+                LexItem::NewLine,
+                LexItem::Word("{@param"),
+                LexItem::Space,
+                LexItem::Word("\\n"),
+                LexItem::Space,
+                LexItem::Word("https://example.com}"),
+                LexItem::NewLine,
+                LexItem::At("@"),
+                LexItem::Word("endcode"),
             ]
         );
     }
 
     #[test]
     fn basic_groups() {
-        let result = lex("@{\n* @name Memory Management\n@}".into());
+        let result = lex("@{\n* @name Memory Management\n@}");
         assert_eq!(
             result,
             vec![
-                LexItem::At("@".into()),
+                LexItem::At("@"),
                 LexItem::Paren('{'),
                 LexItem::NewLine,
-                LexItem::Word("*".into()),
+                LexItem::Word("*"),
                 LexItem::Space,
-                LexItem::At("@".into()),
-                LexItem::Word("name".into()),
+                LexItem::At("@"),
+                LexItem::Word("name"),
                 LexItem::Space,
-                LexItem::Word("Memory".into()),
+                LexItem::Word("Memory"),
                 LexItem::Space,
-                LexItem::Word("Management".into()),
+                LexItem::Word("Management"),
                 LexItem::NewLine,
-                LexItem::At("@".into()),
+                LexItem::At("@"),
                 LexItem::Paren('}')
             ]
         );
     }
+
+    #[test]
+    fn lazy() {
+        let mut lexer = Lexer::new("@b word https://example.com");
+        assert_eq!(lexer.next(), Some(LexItem::At("@")));
+        assert_eq!(lexer.next(), Some(LexItem::Word("b")));
+
+        let rest = lexer.collect::<Vec<_>>();
+        assert_eq!(
+            rest,
+            [
+                LexItem::Space,
+                LexItem::Word("word"),
+                LexItem::Space,
+                LexItem::Url("https://example.com")
+            ]
+        );
+    }
 }
@@ -28,10 +28,58 @@
 //! }
 //! ```
 
-mod emojis;
+/// Emits a debug event with `tracing`, if the `tracing` feature is enabled.
+#[cfg(feature = "tracing")]
+macro_rules! debug {
+    ($($argument:tt)*) => {
+        tracing::debug!($($argument)*)
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! debug {
+    ($($argument:tt)*) => {};
+}
+
+/// Emits a trace event with `tracing`, if the `tracing` feature is enabled.
+#[cfg(feature = "tracing")]
+macro_rules! trace {
+    ($($argument:tt)*) => {
+        tracing::trace!($($argument)*)
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! trace {
+    ($($argument:tt)*) => {};
+}
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::comment::RustdocComment;
+use crate::generator::ParseError;
+use crate::options::Options;
+
+pub mod asciidoc;
+pub mod comment;
+pub mod cst;
+mod diff;
+#[cfg(feature = "emoji")]
+pub mod emojis;
+mod format;
 pub mod generator;
-mod lexer;
+pub mod header;
+pub mod lexer;
+mod markup;
+pub mod options;
 mod parser;
+pub mod rst;
+pub mod source_map;
+pub mod stats;
+pub mod tag;
+pub mod tagfile;
+pub mod transform;
 
 /// This function transforms the Doxygen of a single element (function, struct, etc.)
 ///
@@ -39,5 +87,68 @@ mod parser;
 ///
 /// This function will panic if any error from [`generator::rustdoc`] is returned.
 pub fn transform(value: &str) -> String {
-    generator::rustdoc(value.into()).expect("failed to transform the comments")
+    generator::rustdoc(value.into())
+        .unwrap_or_else(|error| panic!("failed to transform the comments: {error}"))
+}
+
+/// A Doxygen comment, parsed with [`str::parse`] and rendered as Rustdoc with [`fmt::Display`], to
+/// use the translation with generic code.
+///
+/// # Examples
+///
+/// ```
+/// use std::fmt::Write;
+///
+/// use doxygen_rs::DoxygenDoc;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let doc: DoxygenDoc = "@brief Frees a @c buffer".parse()?;
+///
+/// let mut out = String::new();
+/// writeln!(out, "{doc}")?;
+/// assert_eq!(out, "Frees a `buffer`\n");
+/// assert_eq!(doc.comment().brief, "Frees a `buffer`");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct DoxygenDoc {
+    comment: RustdocComment,
+}
+
+impl DoxygenDoc {
+    /// Parses a Doxygen comment, using the given [`Options`].
+    ///
+    /// # Errors
+    ///
+    /// See [`generator::rustdoc_with_options`].
+    pub fn parse_with_options(input: &str, options: &Options) -> Result<Self, ParseError> {
+        comment::convert_with_options(input.into(), options).map(|comment| Self { comment })
+    }
+
+    /// Returns the sections of the translated comment.
+    pub fn comment(&self) -> &RustdocComment {
+        &self.comment
+    }
+}
+
+impl FromStr for DoxygenDoc {
+    type Err = ParseError;
+
+    /// Parses a Doxygen comment, see [`generator::rustdoc`] for the errors.
+    fn from_str(input: &str) -> Result<Self, ParseError> {
+        Self::parse_with_options(input, &Options::default())
+    }
+}
+
+impl From<DoxygenDoc> for RustdocComment {
+    fn from(doc: DoxygenDoc) -> Self {
+        doc.comment
+    }
+}
+
+impl fmt::Display for DoxygenDoc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.comment.fmt(f)
+    }
 }
@@ -0,0 +1,410 @@
+//! Reading of the Markdown of the generated Rustdoc, for its translation to other markup
+//! languages (like AsciiDoc), and the rendering of the sections of a comment in those languages.
+
+use crate::comment::{Param, ReturnValue, RustdocComment, Throws};
+use crate::format;
+use crate::options::{Options, Section};
+use crate::tag::Tag;
+
+/// A block of the Markdown, or a line of a paragraph.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(crate) enum Block<'a> {
+    /// A heading, like `## Title` (level 2).
+    Heading {
+        level: usize,
+        text: &'a str,
+    },
+    /// A fenced code block, with its language (if any).
+    Code {
+        language: &'a str,
+        lines: Vec<&'a str>,
+    },
+    /// A block quote, without the `> ` prefixes of its lines.
+    Quote(String),
+    /// An item of a list, nested `depth` lists deep (starting at 0).
+    ListItem {
+        ordered: bool,
+        depth: usize,
+        text: &'a str,
+    },
+    /// A line of a paragraph, without its indentation.
+    Line(&'a str),
+    Blank,
+}
+
+/// Splits the Markdown into its blocks.
+pub(crate) fn blocks(markdown: &str) -> Vec<Block<'_>> {
+    let lines = markdown.lines().collect::<Vec<_>>();
+    let mut blocks = vec![];
+    let mut list_indents: Vec<usize> = vec![];
+    let mut index = 0;
+
+    while index < lines.len() {
+        let line = lines[index];
+        let content = line.trim_start();
+        index += 1;
+
+        if let Some(language) = content.strip_prefix("```") {
+            let start = index;
+            while index < lines.len() && !lines[index].trim_start().starts_with("```") {
+                index += 1;
+            }
+            blocks.push(Block::Code {
+                language: language.trim(),
+                lines: lines[start..index].to_vec(),
+            });
+            index += 1;
+            continue;
+        }
+
+        if content.starts_with('>') {
+            let start = index - 1;
+            while index < lines.len() && lines[index].trim_start().starts_with('>') {
+                index += 1;
+            }
+            let quote = lines[start..index]
+                .iter()
+                .map(|line| {
+                    let line = &line.trim_start()[1..];
+                    line.strip_prefix(' ').unwrap_or(line)
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            blocks.push(Block::Quote(quote));
+            continue;
+        }
+
+        let level = content.chars().take_while(|&c| c == '#').count();
+        if level > 0 && content[level..].starts_with(' ') {
+            blocks.push(Block::Heading {
+                level,
+                text: content[level..].trim(),
+            });
+            continue;
+        }
+
+        if content.is_empty() {
+            list_indents.clear();
+            blocks.push(Block::Blank);
+            continue;
+        }
+
+        let Some((ordered, text)) = list_item(content) else {
+            blocks.push(Block::Line(content));
+            continue;
+        };
+
+        let indent = line.len() - content.len();
+        while list_indents.last().is_some_and(|&last| last > indent) {
+            list_indents.pop();
+        }
+        if list_indents.last() != Some(&indent) {
+            list_indents.push(indent);
+        }
+
+        blocks.push(Block::ListItem {
+            ordered,
+            depth: list_indents.len() - 1,
+            text,
+        });
+    }
+
+    blocks
+}
+
+/// Splits a list item into whether its list is ordered and its text.
+fn list_item(line: &str) -> Option<(bool, &str)> {
+    if let Some(item) = line.strip_prefix(['*', '-', '+']) {
+        return item.strip_prefix(' ').map(|item| (false, item));
+    }
+
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    match digits {
+        0 => None,
+        _ => line[digits..].strip_prefix(". ").map(|item| (true, item)),
+    }
+}
+
+/// The inline markup of a language, for [`inline`].
+pub(crate) trait Inline {
+    /// A literal monospace text, from `` `code` ``.
+    fn code(&self, code: &str) -> String;
+
+    /// An italic text (already translated), from `_text_` or `*text*`.
+    fn italics(&self, text: &str) -> String;
+
+    /// A link with the Markdown `label`, from `[label](url)`.
+    fn link(&self, label: &str, url: &str) -> String;
+
+    /// A link to an anchor of the documentation, from `[label](#anchor)`.
+    fn reference(&self, label: &str, anchor: &str) -> String;
+
+    /// A link showing its URL, from `<url>`.
+    fn autolink(&self, url: &str) -> String;
+}
+
+/// Translates the Markdown within a line: the inline code, the italics and the links. The bold
+/// text is left as it is, being the same in the supported languages, and the intra-doc links
+/// (like ``[`Foo`]``) are left as their label, as they have no target outside of Rustdoc.
+pub(crate) fn inline(text: &str, markup: &impl Inline) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        match c {
+            '`' => {
+                let ticks = rest.chars().take_while(|&c| c == '`').count();
+                let delimiter = &rest[..ticks];
+                if let Some(end) = rest[ticks..].find(delimiter) {
+                    result += &markup.code(rest[ticks..ticks + end].trim());
+                    rest = &rest[ticks + end + ticks..];
+                    continue;
+                }
+                result += delimiter;
+                rest = &rest[ticks..];
+                continue;
+            }
+            '[' => {
+                if let Some((link, length)) = link(rest, markup) {
+                    result += &link;
+                    rest = &rest[length..];
+                    continue;
+                }
+            }
+            '<' => {
+                let url = rest[1..].split_once('>').map(|(url, _)| url);
+                if let Some(url) = url.filter(|url| url.contains(':') && !url.contains(' ')) {
+                    result += &markup.autolink(url);
+                    rest = &rest[url.len() + 2..];
+                    continue;
+                }
+            }
+            '_' if !text[..text.len() - rest.len()].ends_with(char::is_alphanumeric) => {
+                let end = rest[1..]
+                    .match_indices('_')
+                    .map(|(end, _)| end)
+                    .find(|&end| !rest[end + 2..].starts_with(char::is_alphanumeric));
+                if let Some(end) = end.filter(|&end| end > 0 && !rest[1..].starts_with(' ')) {
+                    result += &markup.italics(&inline(&rest[1..1 + end], markup));
+                    rest = &rest[end + 2..];
+                    continue;
+                }
+            }
+            '*' if rest.starts_with("**") => {
+                result += "**";
+                rest = &rest[2..];
+                continue;
+            }
+            '*' if !rest[1..].starts_with(' ') => {
+                if let Some(end) = rest[1..].find('*').filter(|&end| end > 0) {
+                    result += &markup.italics(&inline(&rest[1..1 + end], markup));
+                    rest = &rest[end + 2..];
+                    continue;
+                }
+            }
+            _ => {}
+        }
+
+        result.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+
+    result
+}
+
+/// Translates the Markdown link at the start of the text, like `[text](url)` or an intra-doc link
+/// like ``[`Foo`]``, returning it with the length of the Markdown.
+fn link(text: &str, markup: &impl Inline) -> Option<(String, usize)> {
+    let end = text.find(']')?;
+    let label = &text[1..end];
+
+    match text[end + 1..].strip_prefix('(') {
+        Some(rest) => {
+            let url_end = rest.find(')')?;
+            let url = rest[..url_end]
+                .trim_start_matches('<')
+                .trim_end_matches('>');
+            let link = match url.strip_prefix('#') {
+                Some(anchor) => markup.reference(label, anchor),
+                None => markup.link(label, url),
+            };
+            Some((link, end + 2 + url_end + 1))
+        }
+        None if label.starts_with('`') => Some((inline(label, markup), end + 1)),
+        None => None,
+    }
+}
+
+/// The sections of a comment in a language, for [`render`]. Each section is rendered as a block,
+/// left out if empty.
+pub(crate) trait Sections {
+    /// A text of the Rustdoc, like the description.
+    fn markdown(&self, text: &str, options: &Options) -> String;
+
+    /// An admonition, from `@note`, `@warning`, `@important`, `@deprecated` or `@since`.
+    fn admonition(&self, tag: &Tag, text: &str, options: &Options) -> String;
+
+    /// A list with a title, like the preconditions.
+    fn titled_list(&self, title: &str, items: &[String], options: &Options) -> String;
+
+    fn arguments(&self, params: &[Param], options: &Options) -> String;
+
+    fn returns(&self, returns: Option<&str>, retvals: &[ReturnValue], options: &Options) -> String;
+
+    fn errors(&self, throws: &[Throws], options: &Options) -> String;
+
+    /// The preconditions, with [`Options::pre_as_safety`].
+    fn safety(&self, preconditions: &[String], options: &Options) -> String;
+
+    fn see_also(&self, targets: &[String], options: &Options) -> String;
+}
+
+/// Renders the sections of a translated comment, in the order of [`Options::section_order`] like
+/// the Rustdoc.
+pub(crate) fn render(
+    comment: &RustdocComment,
+    options: &Options,
+    markup: &impl Sections,
+) -> String {
+    let mut blocks = vec![];
+    if !comment.brief.is_empty() {
+        blocks.push(markup.markdown(&comment.brief, options));
+    }
+
+    let order = options.section_order.iter().chain(
+        Section::DEFAULT_ORDER
+            .iter()
+            .filter(|section| !options.section_order.contains(section)),
+    );
+    for section in order {
+        match section {
+            Section::Description => {
+                blocks.push(markup.markdown(&comment.description, options));
+                let admonitions = [
+                    (Tag::Note, &comment.notes),
+                    (Tag::Warning, &comment.warnings),
+                    (Tag::Important, &comment.important),
+                ];
+                for (tag, texts) in admonitions {
+                    blocks.extend(
+                        texts
+                            .iter()
+                            .map(|text| markup.admonition(&tag, text, options)),
+                    );
+                }
+                if !options.pre_as_safety {
+                    blocks.push(markup.titled_list(
+                        "Preconditions",
+                        &comment.preconditions,
+                        options,
+                    ));
+                }
+                blocks.push(markup.titled_list("Postconditions", &comment.postconditions, options));
+                if let Some(deprecated) = &comment.deprecated {
+                    blocks.push(markup.admonition(&Tag::Deprecated, deprecated, options));
+                }
+                if let Some(since) = &comment.since {
+                    blocks.push(markup.admonition(&Tag::Since, since, options));
+                }
+            }
+            Section::Arguments => blocks.push(markup.arguments(&comment.params, options)),
+            Section::Returns => {
+                blocks.push(markup.returns(comment.returns.as_deref(), &comment.retvals, options))
+            }
+            Section::Errors => blocks.push(markup.errors(&comment.throws, options)),
+            Section::Safety if options.pre_as_safety => {
+                blocks.push(markup.safety(&comment.preconditions, options));
+            }
+            Section::SeeAlso => blocks.push(markup.see_also(&comment.see_also, options)),
+            // The code blocks gathered in the Rustdoc are part of the description here
+            Section::Safety | Section::Examples => {}
+        }
+    }
+
+    blocks.retain(|block| !block.is_empty());
+    let result = blocks.join("\n\n");
+
+    match &options.line_prefix {
+        Some(prefix) => format::prefix_lines(&result, prefix),
+        None => result,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct Html;
+
+    impl Inline for Html {
+        fn code(&self, code: &str) -> String {
+            format!("<code>{code}</code>")
+        }
+
+        fn italics(&self, text: &str) -> String {
+            format!("<em>{text}</em>")
+        }
+
+        fn link(&self, label: &str, url: &str) -> String {
+            format!("<a href=\"{url}\">{}</a>", inline(label, self))
+        }
+
+        fn reference(&self, label: &str, anchor: &str) -> String {
+            self.link(label, &format!("#{anchor}"))
+        }
+
+        fn autolink(&self, url: &str) -> String {
+            self.link(url, url)
+        }
+    }
+
+    #[test]
+    fn splits_blocks() {
+        let markdown = "# Title\n\n* A\n  1. B\n* C\n\n> Quote\n> lines\n```c\nx\n```\nText";
+
+        assert_eq!(
+            blocks(markdown),
+            vec![
+                Block::Heading {
+                    level: 1,
+                    text: "Title",
+                },
+                Block::Blank,
+                Block::ListItem {
+                    ordered: false,
+                    depth: 0,
+                    text: "A",
+                },
+                Block::ListItem {
+                    ordered: true,
+                    depth: 1,
+                    text: "B",
+                },
+                Block::ListItem {
+                    ordered: false,
+                    depth: 0,
+                    text: "C",
+                },
+                Block::Blank,
+                Block::Quote("Quote\nlines".into()),
+                Block::Code {
+                    language: "c",
+                    lines: vec!["x"],
+                },
+                Block::Line("Text"),
+            ]
+        );
+    }
+
+    #[test]
+    fn translates_inline_markup() {
+        assert_eq!(
+            inline(
+                "A **bold** *it `x`* _it_, snake_case_name, [`Foo`], [*docs*](<https://a.b>) and <mailto:c@d> < e > f",
+                &Html
+            ),
+            "A **bold** <em>it <code>x</code></em> <em>it</em>, snake_case_name, <code>Foo</code>, <a href=\"https://a.b\"><em>docs</em></a> and <a href=\"mailto:c@d\">mailto:c@d</a> < e > f"
+        );
+    }
+}
@@ -0,0 +1,380 @@
+//! Configuration for the Doxygen to Rustdoc translation.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::tagfile::TagFile;
+
+/// Options used by [`crate::generator::rustdoc_with_options`].
+///
+/// # Examples
+///
+/// ```
+/// use doxygen_rs::generator::rustdoc_with_options;
+/// use doxygen_rs::options::Options;
+///
+/// let options = Options {
+///     strict: true,
+///     ..Default::default()
+/// };
+///
+/// assert!(rustdoc_with_options("@mytag Example".into(), &options).is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// Return a [`ParseError::UnknownTag`](crate::generator::ParseError::UnknownTag) instead of
+    /// silently dropping tags that can't be translated, and a
+    /// [`ParseError::UnknownEmoji`](crate::generator::ParseError::UnknownEmoji) instead of keeping
+    /// the shortcodes of unknown emojis.
+    pub strict: bool,
+    /// Emit tags that can't be translated literally (like `@mytag`), instead of dropping them.
+    ///
+    /// Ignored if [`Options::strict`] is enabled.
+    pub keep_unknown_tags: bool,
+    /// Wrap the generated lines at the given column. Lines are never wrapped if `None`.
+    pub wrap: Option<usize>,
+    /// The prefix of every generated line, like `/// ` or four spaces, to splice the Rustdoc into
+    /// generated source code or nested Markdown as it is. The prefix counts in the width of
+    /// [`Options::wrap`], and is trimmed on the blank lines.
+    pub line_prefix: Option<String>,
+    /// Note the tag and the line of the input every part of the Rustdoc was generated from, with
+    /// HTML comments like `<!-- from @param line 12 -->`, to find the origin of the mistranslations
+    /// in large inputs. Meant for debugging, as the comments can change how the Rustdoc renders.
+    pub provenance: bool,
+    /// The style used for the headings of the sections generated from the tags, like
+    /// `# Arguments`.
+    pub heading_style: HeadingStyle,
+    /// The level (from 1 to 6) of the generated headings, including the ones from `@par` and
+    /// groups. Defaults to 1 (`# `), use a higher level to nest them under other headings.
+    pub heading_level: usize,
+    /// How the direction of a `@param` (like `@param[in]`) is rendered.
+    pub direction_style: DirectionStyle,
+    /// How the admonitions, like `@note` and `@warning`, are rendered.
+    pub admonition_style: AdmonitionStyle,
+    /// The order of the sections of the Rustdoc, after the brief (which is always the first
+    /// paragraph). The sections left out are rendered after the others, in the default order:
+    /// [`Section::DEFAULT_ORDER`].
+    pub section_order: Vec<Section>,
+    /// Use `# Errors` instead of `# Throws` for the section generated from `@throws`, as the Rust
+    /// API guidelines do.
+    pub throws_as_errors: bool,
+    /// The Rust paths of the error types reported instead of the exceptions of `@throws`, by
+    /// exception type, like `std::runtime_error` to `crate::Error::Runtime`. The exceptions that
+    /// aren't in the table are linked as they are, to their URL in
+    /// [`external_links`](Self::external_links) if any.
+    pub error_types: HashMap<String, String>,
+    /// Render the preconditions from `@pre` under a `# Safety` section, as they're effectively the
+    /// safety requirements of `unsafe` functions (like the FFI ones).
+    pub pre_as_safety: bool,
+    /// The description used for the parameters and return values that don't have one. If `None`,
+    /// only their name is rendered.
+    pub missing_description: Option<String>,
+    /// Convert the HTML links with a text, like `<a href="https://example.com">Example</a>`, to
+    /// Markdown links like `[Example](https://example.com)`.
+    pub markdown_links: bool,
+    /// The languages of the code blocks from `@code{.ext}`, by extension (without the dot).
+    /// Extensions that aren't in the table are used as the language.
+    pub code_languages: HashMap<String, String>,
+    /// The language of the code blocks from `@code` without an extension. Defaults to `text`, as
+    /// rustdoc runs the code blocks without a language as Rust doctests, which the examples of C
+    /// and C++ APIs aren't. If `None`, the code blocks are left without a language.
+    pub default_code_language: Option<String>,
+    /// The language of the code blocks from `@verbatim`. Defaults to `text`, which rustdoc renders
+    /// as-is without running it. If `None`, the code blocks are left without a language.
+    pub verbatim_language: Option<String>,
+    /// The directories searched (in order) for the files of `@dotfile` and `@mscfile`, like the
+    /// `DOTFILE_DIRS` and `MSCFILE_DIRS` of Doxygen. The diagrams found are rendered as code blocks
+    /// (in the `dot` and `msc` languages), and the others (like the ones of `@diafile`, which
+    /// aren't text) as links to their file.
+    pub include_paths: Vec<PathBuf>,
+    /// Translate `@emoji` to the emoji. If disabled, the shortcode (like `:smile:`) is rendered
+    /// instead, as it is for the built-in emojis without the `emoji` feature (enabled by default).
+    pub expand_emojis: bool,
+    /// Emojis used by `@emoji`, by shortcode (without the colons). These take precedence over the
+    /// built-in ones, so they can also override them, and are translated even without the `emoji`
+    /// feature.
+    pub custom_emojis: HashMap<String, String>,
+    /// The marker of the items of the generated lists, like the parameters.
+    pub list_marker: ListMarker,
+    /// The indentation (in spaces) of the lines continuing a list item, like the description of a
+    /// `@param` spanning multiple lines. If `None`, the lines are kept as they are, and the lines
+    /// wrapped with [`Options::wrap`] are aligned with the text after the marker.
+    pub list_indent: Option<usize>,
+    /// Names of nonstandard tags, mapped to the known tags they're translated as, like
+    /// `returns_value` to `retval`. The tags are renamed before anything else, so an alias can't
+    /// refer to another one.
+    pub tag_aliases: HashMap<String, String>,
+    /// Move the code blocks from `@code` to an `# Examples` section at the end, as the Rust API
+    /// guidelines do.
+    pub gather_examples: bool,
+    /// Limits on the size of the input, to reject the pathological comments (like the ones of
+    /// untrusted sources) instead of spending time and memory on them.
+    pub limits: Limits,
+    /// Translate identical comments only once in the [`crate::transform`] functions, reusing the
+    /// translation of the first one. Worth it for bindings repeating the same comments, like the
+    /// ones of macro-generated APIs.
+    pub cache_comments: bool,
+    /// The tag files of other projects, to link the `@ref`, `@see` and `@sa` targets they document
+    /// to their documentation. See [`TagFile`].
+    pub tag_files: Vec<TagFile>,
+    /// The URLs of the documentation of symbols that can't be linked with intra-doc links, used
+    /// for the `@ref`, `@see` and `@sa` targets that aren't in [`Options::tag_files`]. The symbols
+    /// are written without their arguments, like `std::vector` or `mmap`. Defaults to common
+    /// symbols of the C and C++ standard libraries (on cppreference) and of POSIX (on man7),
+    /// leaving out the names generic enough to be used by the crate itself, like `open`, `free` or
+    /// `FILE`.
+    pub external_links: HashMap<String, String>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            strict: false,
+            keep_unknown_tags: false,
+            wrap: None,
+            line_prefix: None,
+            provenance: false,
+            heading_style: HeadingStyle::default(),
+            heading_level: 1,
+            direction_style: DirectionStyle::default(),
+            admonition_style: AdmonitionStyle::default(),
+            section_order: Section::DEFAULT_ORDER.to_vec(),
+            throws_as_errors: false,
+            error_types: HashMap::new(),
+            pre_as_safety: false,
+            missing_description: None,
+            markdown_links: false,
+            code_languages: default_code_languages(),
+            default_code_language: Some("text".into()),
+            verbatim_language: Some("text".into()),
+            include_paths: vec![],
+            expand_emojis: true,
+            custom_emojis: HashMap::new(),
+            list_marker: ListMarker::default(),
+            list_indent: None,
+            tag_aliases: HashMap::new(),
+            gather_examples: false,
+            limits: Limits::default(),
+            cache_comments: false,
+            tag_files: vec![],
+            external_links: default_external_links(),
+        }
+    }
+}
+
+/// The default languages of the code blocks, by extension.
+fn default_code_languages() -> HashMap<String, String> {
+    [
+        ("c", "c"),
+        ("h", "c"),
+        ("cc", "cpp"),
+        ("cpp", "cpp"),
+        ("cxx", "cpp"),
+        ("hpp", "cpp"),
+        ("m", "objc"),
+        ("py", "python"),
+        ("rs", "rust"),
+        ("java", "java"),
+        ("js", "javascript"),
+        ("cs", "csharp"),
+        ("sh", "sh"),
+        ("xml", "xml"),
+        ("json", "json"),
+        ("unparsed", "text"),
+    ]
+    .into_iter()
+    .map(|(extension, language)| (extension.into(), language.into()))
+    .collect()
+}
+
+/// The default URLs of the symbols of the standard libraries.
+fn default_external_links() -> HashMap<String, String> {
+    let cppreference = [
+        ("std::array", "cpp/container/array"),
+        ("std::deque", "cpp/container/deque"),
+        ("std::list", "cpp/container/list"),
+        ("std::map", "cpp/container/map"),
+        ("std::set", "cpp/container/set"),
+        ("std::span", "cpp/container/span"),
+        ("std::unordered_map", "cpp/container/unordered_map"),
+        ("std::unordered_set", "cpp/container/unordered_set"),
+        ("std::vector", "cpp/container/vector"),
+        ("std::string", "cpp/string/basic_string"),
+        ("std::string_view", "cpp/string/basic_string_view"),
+        ("std::unique_ptr", "cpp/memory/unique_ptr"),
+        ("std::shared_ptr", "cpp/memory/shared_ptr"),
+        ("std::weak_ptr", "cpp/memory/weak_ptr"),
+        ("std::bad_alloc", "cpp/memory/new/bad_alloc"),
+        ("std::function", "cpp/utility/functional/function"),
+        ("std::optional", "cpp/utility/optional"),
+        ("std::pair", "cpp/utility/pair"),
+        ("std::tuple", "cpp/utility/tuple"),
+        ("std::variant", "cpp/utility/variant"),
+        ("std::mutex", "cpp/thread/mutex"),
+        ("std::thread", "cpp/thread/thread"),
+        ("std::exception", "cpp/error/exception"),
+        ("std::invalid_argument", "cpp/error/invalid_argument"),
+        ("std::out_of_range", "cpp/error/out_of_range"),
+        ("std::runtime_error", "cpp/error/runtime_error"),
+        ("std::size_t", "cpp/types/size_t"),
+        ("fopen", "c/io/fopen"),
+        ("fclose", "c/io/fclose"),
+        ("fread", "c/io/fread"),
+        ("fwrite", "c/io/fwrite"),
+        ("fprintf", "c/io/fprintf"),
+        ("snprintf", "c/io/fprintf"),
+        ("malloc", "c/memory/malloc"),
+        ("calloc", "c/memory/calloc"),
+        ("realloc", "c/memory/realloc"),
+        ("memcpy", "c/string/byte/memcpy"),
+        ("memmove", "c/string/byte/memmove"),
+        ("memset", "c/string/byte/memset"),
+        ("strcmp", "c/string/byte/strcmp"),
+        ("strlen", "c/string/byte/strlen"),
+        ("strncpy", "c/string/byte/strncpy"),
+        ("qsort", "c/algorithm/qsort"),
+        ("atexit", "c/program/atexit"),
+    ]
+    .map(|(symbol, page)| (symbol, format!("https://en.cppreference.com/w/{page}")));
+
+    let man_pages = [
+        ("ioctl", 2),
+        ("mmap", 2),
+        ("munmap", 2),
+        ("dlopen", 3),
+        ("dlsym", 3),
+        ("errno", 3),
+        ("getenv", 3),
+        ("pthread_create", 3),
+        ("pthread_join", 3),
+        ("strerror", 3),
+    ]
+    .map(|(name, section)| {
+        let url = format!("https://man7.org/linux/man-pages/man{section}/{name}.{section}.html");
+        (name, url)
+    });
+
+    cppreference
+        .into_iter()
+        .chain(man_pages)
+        .map(|(symbol, url)| (symbol.into(), url))
+        .collect()
+}
+
+/// The sections of the generated Rustdoc, see [`Options::section_order`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Section {
+    /// The text that isn't part of the other sections, including the block quotes (like the ones
+    /// of `@note`) and the headings of `@par`.
+    Description,
+    /// The parameters, from `@param`.
+    Arguments,
+    /// The return value, from `@return` and `@retval`.
+    Returns,
+    /// The exceptions, from `@throws`, under `# Throws` or `# Errors` (see
+    /// [`Options::throws_as_errors`]).
+    Errors,
+    /// The preconditions rendered with [`Options::pre_as_safety`].
+    Safety,
+    /// The references, from `@see` and `@sa`.
+    SeeAlso,
+    /// The code blocks gathered with [`Options::gather_examples`].
+    Examples,
+}
+
+impl Section {
+    /// The default order of the sections.
+    pub const DEFAULT_ORDER: [Section; 7] = [
+        Section::Description,
+        Section::Arguments,
+        Section::Returns,
+        Section::Errors,
+        Section::Safety,
+        Section::SeeAlso,
+        Section::Examples,
+    ];
+}
+
+/// Styles for the headings of the generated sections.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum HeadingStyle {
+    /// Markdown headings, like `# Arguments`.
+    #[default]
+    Heading,
+    /// Bold labels, like `**Arguments:**`, for embedding the Rustdoc under existing headings.
+    Bold,
+}
+
+/// Styles for the direction of the parameters.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum DirectionStyle {
+    /// Describe the direction, like ``* `example` (direction in, out) -``.
+    #[default]
+    Verbose,
+    /// Add the direction after the name, like ``* `example` [in,out] -``.
+    Compact,
+    /// Don't render the direction.
+    Omit,
+}
+
+/// Styles for the admonitions: `@note`, `@warning`, `@attention`, `@important`, `@deprecated` and
+/// `@since`. The remarks are always rendered as plain block quotes.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub enum AdmonitionStyle {
+    /// Block quotes with a bold label, like `> **Note:** Text.`. `@remark` is quoted without a
+    /// label.
+    #[default]
+    Quote,
+    /// Paragraphs starting with a bold label, like `**Note:** Text.`, for the renderers without
+    /// block quotes.
+    Label,
+    /// The alerts of GitHub Flavored Markdown, like `> [!NOTE]` followed by the text, which both
+    /// GitHub and rustdoc render as callouts. `@attention` is rendered as a warning,
+    /// `@deprecated` as a caution, and `@since` and `@remark` as block quotes.
+    Alert,
+    /// The start of the admonitions, followed by their text, where `{label}` is replaced by their
+    /// label (like `Note`, `Remark` or `Available since`). The templates starting with `>` are block quotes,
+    /// continued on every line of the text.
+    Template(String),
+}
+
+/// Markers of the items of the generated lists.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum ListMarker {
+    /// Asterisks, like ``* `example` - An example.``.
+    #[default]
+    Asterisk,
+    /// Dashes, like ``- `example` - An example.``.
+    Dash,
+}
+
+impl ListMarker {
+    /// The marker, like `*`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ListMarker::Asterisk => "*",
+            ListMarker::Dash => "-",
+        }
+    }
+}
+
+/// Limits on the input. Exceeding one makes the translation fail with a
+/// [`ParseError::LimitExceeded`](crate::generator::ParseError::LimitExceeded). There are no limits
+/// by default.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct Limits {
+    /// The maximum length of the input, in bytes.
+    pub max_input_len: Option<usize>,
+    /// The maximum nesting of the groups (like `@{`).
+    pub max_group_depth: Option<usize>,
+    /// The maximum number of tokens (words, spaces, tags, etc.) of the input.
+    pub max_tokens: Option<usize>,
+}
+
+/// The limits of [`Limits`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Limit {
+    InputLength,
+    GroupDepth,
+    Tokens,
+}
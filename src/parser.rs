@@ -1,117 +1,523 @@
-use crate::lexer::{lex, LexItem};
+use std::borrow::Cow;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::ops::Range;
+
+use crate::lexer::{span_of, LexItem, Lexer};
+use crate::options::{Limit, Options};
+use crate::tag::Tag;
 
 const OPEN_PAREN: char = '{';
 const CLOSED_PAREN: char = '}';
 
-#[derive(Debug, Clone)]
+/// An error found while translating a comment. The lines start at 1, and the spans are the byte
+/// ranges of the notations in the input (like `0..8` for `@param a` in `@param a The value.`).
+///
+/// The errors are displayed as a message with their line, and each kind has a stable code (see
+/// [`ParseError::code`]) to filter them in tools.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[non_exhaustive]
 pub enum ParseError {
+    /// The input ended in the middle of a notation.
     UnexpectedEndOfInput,
+    /// A notation has an invalid part, like the direction of `@param[sideways]`.
     UnexpectedInput {
         found: String,
         expected: Vec<String>,
+        line: usize,
+        span: Range<usize>,
+    },
+    /// A tag isn't known, in [strict](crate::options::Options::strict) mode.
+    UnknownTag {
+        tag: String,
+        line: usize,
+        span: Range<usize>,
+    },
+    /// A tag requiring a word, like `@b`, is the last thing of the comment.
+    MissingParameter {
+        tag: String,
+        line: usize,
+        span: Range<usize>,
+    },
+    /// An `@emoji` isn't known, in [strict](crate::options::Options::strict) mode.
+    UnknownEmoji {
+        emoji: String,
+        line: usize,
+        span: Range<usize>,
+    },
+    /// A `@param` has no name.
+    MissingParamName {
+        tag: String,
+        line: usize,
+        span: Range<usize>,
+    },
+    /// A parameter has a name but no description.
+    MissingDescription {
+        tag: String,
+        name: String,
+        line: usize,
+        span: Range<usize>,
+    },
+    /// A tag without equivalent in Rustdoc, like `@addindex`, was removed.
+    IgnoredTag {
+        tag: String,
+        line: usize,
+        span: Range<usize>,
+    },
+    /// An end of block, like `@endcode`, doesn't end any block and was removed.
+    UnmatchedTag {
+        tag: String,
+        line: usize,
+        span: Range<usize>,
     },
+    /// The output couldn't be written.
+    WriteFailed,
+    /// The input exceeds one of the [`Options::limits`].
+    LimitExceeded { limit: Limit, max: usize },
+}
+
+impl ParseError {
+    /// Returns the stable code of the kind of error, like `E003` for [`ParseError::UnknownTag`].
+    pub fn code(&self) -> &'static str {
+        match self {
+            ParseError::UnexpectedEndOfInput => "E001",
+            ParseError::UnexpectedInput { .. } => "E002",
+            ParseError::UnknownTag { .. } => "E003",
+            ParseError::MissingParameter { .. } => "E004",
+            ParseError::UnknownEmoji { .. } => "E005",
+            ParseError::MissingDescription { .. } => "E006",
+            ParseError::IgnoredTag { .. } => "E007",
+            ParseError::WriteFailed => "E008",
+            ParseError::MissingParamName { .. } => "E009",
+            ParseError::LimitExceeded { .. } => "E010",
+            ParseError::UnmatchedTag { .. } => "E011",
+        }
+    }
+
+    /// Returns the line of the input the error was found at, if any.
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            ParseError::UnexpectedInput { line, .. }
+            | ParseError::UnknownTag { line, .. }
+            | ParseError::MissingParameter { line, .. }
+            | ParseError::UnknownEmoji { line, .. }
+            | ParseError::MissingParamName { line, .. }
+            | ParseError::MissingDescription { line, .. }
+            | ParseError::IgnoredTag { line, .. }
+            | ParseError::UnmatchedTag { line, .. } => Some(*line),
+            ParseError::UnexpectedEndOfInput
+            | ParseError::WriteFailed
+            | ParseError::LimitExceeded { .. } => None,
+        }
+    }
+
+    /// Returns the byte range of the input the error was found at, if any.
+    pub fn span(&self) -> Option<Range<usize>> {
+        match self {
+            ParseError::UnexpectedInput { span, .. }
+            | ParseError::UnknownTag { span, .. }
+            | ParseError::MissingParameter { span, .. }
+            | ParseError::UnknownEmoji { span, .. }
+            | ParseError::MissingParamName { span, .. }
+            | ParseError::MissingDescription { span, .. }
+            | ParseError::IgnoredTag { span, .. }
+            | ParseError::UnmatchedTag { span, .. } => Some(span.clone()),
+            ParseError::UnexpectedEndOfInput
+            | ParseError::WriteFailed
+            | ParseError::LimitExceeded { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(line) = self.line() {
+            write!(f, "line {line}: ")?;
+        }
+
+        match self {
+            ParseError::UnexpectedEndOfInput => write!(f, "unexpected end of the comment"),
+            ParseError::UnexpectedInput {
+                found, expected, ..
+            } => {
+                let expected = expected
+                    .iter()
+                    .map(|expected| format!("`{expected}`"))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                write!(f, "found `{found}`, expected one of {expected}")
+            }
+            ParseError::UnknownTag { tag, .. } => write!(f, "unknown tag `@{tag}`"),
+            ParseError::MissingParameter { tag, .. } => {
+                write!(f, "`@{tag}` must be followed by a word")
+            }
+            ParseError::UnknownEmoji { emoji, .. } => write!(f, "unknown emoji `{emoji}`"),
+            ParseError::MissingParamName { tag, .. } => {
+                write!(f, "`@{tag}` must be followed by the name of the parameter")
+            }
+            ParseError::MissingDescription { tag, name, .. } => {
+                write!(f, "`@{tag} {name}` has no description")
+            }
+            ParseError::IgnoredTag { tag, .. } => {
+                write!(f, "`@{tag}` has no equivalent in Rustdoc and was removed")
+            }
+            ParseError::UnmatchedTag { tag, .. } => {
+                write!(f, "`@{tag}` doesn't end any block and was removed")
+            }
+            ParseError::WriteFailed => write!(f, "failed to write the output"),
+            ParseError::LimitExceeded { limit, max } => {
+                let limit = match limit {
+                    Limit::InputLength => "is longer than",
+                    Limit::GroupDepth => "nests more groups than",
+                    Limit::Tokens => "has more tokens than",
+                };
+                write!(f, "the comment {limit} the limit of {max}")
+            }
+        }
+    }
 }
 
+impl std::error::Error for ParseError {}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub(crate) enum GrammarItem {
     Notation {
         meta: Vec<String>,
         params: Vec<String>,
-        tag: String,
+        tag: Tag,
+        line: usize,
+        /// The byte range of the notation in the input, from the `@` to its last parameter
+        span: Range<usize>,
     },
     Text(String),
+    /// The target of a Markdown link to a `@ref`, like `target` in `[text](@ref target)`.
+    LinkTarget {
+        target: String,
+        line: usize,
+    },
     GroupStart,
     GroupEnd,
 }
 
+#[cfg(test)]
 pub(crate) fn parse(input: String) -> Result<Vec<GrammarItem>, ParseError> {
-    let mut lexed = lex(input);
-    lexed.push(LexItem::Space);
-    lexed.push(LexItem::Space);
-    lexed.push(LexItem::Space);
-    parse_items(lexed)
+    parse_recovering(&input, &Options::default(), None)
+}
+
+/// Parses the input, pushing the errors into `warnings` (if any) and recovering from them by
+/// keeping the offending notation as text. The tags are renamed with [`Options::tag_aliases`]
+/// first. Exceeding the [`Options::limits`] is always an error.
+pub(crate) fn parse_recovering(
+    input: &str,
+    options: &Options,
+    warnings: Option<&mut Vec<ParseError>>,
+) -> Result<Vec<GrammarItem>, ParseError> {
+    let limits = options.limits;
+    check_limit(Limit::InputLength, input.len(), limits.max_input_len)?;
+
+    let mut tokens = Tokens {
+        input,
+        lexer: Lexer::new(input),
+        lookahead: VecDeque::new(),
+        lexed: 0,
+        max: limits.max_tokens,
+    };
+    let parsed = parse_items(&mut tokens, &options.tag_aliases, warnings)?;
+    trace!(tokens = tokens.lexed, "lexed the input");
+    trace!(items = ?parsed, "parsed the tokens");
+
+    if let Some(max) = limits.max_group_depth {
+        let mut depth = 0usize;
+        for item in &parsed {
+            match item {
+                GrammarItem::GroupStart => depth += 1,
+                GrammarItem::GroupEnd => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+
+            check_limit(Limit::GroupDepth, depth, Some(max))?;
+        }
+    }
+
+    Ok(parsed)
+}
+
+/// The tokens of the input, lexed as the parser reads them rather than all at once.
+struct Tokens<'a> {
+    input: &'a str,
+    lexer: Lexer<'a>,
+    /// The tokens lexed ahead of the current one
+    lookahead: VecDeque<LexItem<'a>>,
+    /// The number of tokens lexed so far
+    lexed: usize,
+    /// The maximum number of tokens, where the lexing stops as the input is rejected anyway
+    max: Option<usize>,
+}
+
+impl<'a> Tokens<'a> {
+    /// Lexes the next token of the input, erroring once there are too many tokens.
+    fn lex(&mut self) -> Result<Option<LexItem<'a>>, ParseError> {
+        let Some(token) = self.lexer.next() else {
+            return Ok(None);
+        };
+
+        self.lexed += 1;
+        check_limit(Limit::Tokens, self.lexed, self.max)?;
+        Ok(Some(token))
+    }
+
+    /// Returns the next token, if any.
+    fn next(&mut self) -> Result<Option<LexItem<'a>>, ParseError> {
+        match self.lookahead.pop_front() {
+            Some(token) => Ok(Some(token)),
+            None => self.lex(),
+        }
+    }
+
+    /// Returns the tokens following the current one. Only the next token is lexed, unless the
+    /// current one is an `@`, where the parameters of the command up to the end of the line (or the
+    /// next command) are lexed too.
+    fn following(&mut self, after_at: bool) -> Result<&[LexItem<'a>], ParseError> {
+        let ends_parameters = |token: &LexItem| matches!(token, LexItem::NewLine | LexItem::At(_));
+        let mut ended = !after_at || self.lookahead.iter().skip(1).any(ends_parameters);
+        let minimum = if after_at { 3 } else { 1 };
+
+        while self.lookahead.len() < minimum || !ended {
+            let Some(token) = self.lex()? else {
+                break;
+            };
+
+            ended |= !self.lookahead.is_empty() && ends_parameters(&token);
+            self.lookahead.push_back(token);
+        }
+
+        Ok(self.lookahead.make_contiguous())
+    }
 }
 
-fn parse_items(input: Vec<LexItem>) -> Result<Vec<GrammarItem>, ParseError> {
+/// Errors if the value exceeds the maximum (if any).
+fn check_limit(limit: Limit, value: usize, max: Option<usize>) -> Result<(), ParseError> {
+    match max {
+        Some(max) if value > max => {
+            debug!(?limit, value, max, "exceeded a limit");
+            Err(ParseError::LimitExceeded { limit, max })
+        }
+        _ => Ok(()),
+    }
+}
+
+fn parse_items(
+    tokens: &mut Tokens,
+    aliases: &HashMap<String, String>,
+    mut warnings: Option<&mut Vec<ParseError>>,
+) -> Result<Vec<GrammarItem>, ParseError> {
+    let input = tokens.input;
     let mut grammar_items = vec![];
     let mut param_iter_skip_count = 0;
+    let mut line = 1;
+    // Whether the items are in a code or verbatim region, where all the spaces are kept
+    let mut in_code = false;
+    // Whether the items are in a region for another output format (like `@docbookonly`), which is
+    // dropped
+    let mut in_foreign_region = false;
 
-    for item in input.windows(4) {
-        let current = item.get(0).unwrap();
-        let next = item.get(1);
+    while let Some(current) = tokens.next()? {
+        let following = tokens.following(matches!(current, LexItem::At(_)))?;
+        let next = following.first();
+        // The name of the command following an `@` (if any), renamed with the aliases
+        let command = match (current, next) {
+            (LexItem::At(_), Some(LexItem::Word(word))) => rename_tag(word, aliases),
+            _ => Cow::Borrowed(""),
+        };
+
+        if in_foreign_region && param_iter_skip_count == 0 {
+            match (current, next) {
+                (LexItem::At(_), Some(LexItem::Word(_)))
+                    if Tag::from(command.as_ref()).is_foreign_region() =>
+                {
+                    in_foreign_region = false;
+                }
+                (LexItem::NewLine, _) => {
+                    line += 1;
+                    continue;
+                }
+                _ => continue,
+            }
+        }
 
         match current {
-            LexItem::At(_) => {
+            LexItem::At(prefix) => {
                 if let Some(next) = next {
                     match next {
+                        // Not a command, like `@3` or `@example.com`
+                        LexItem::Word(_) if !is_command(&command) => {
+                            push_text(&mut grammar_items, &format!("{prefix}{command}"));
+                            param_iter_skip_count = 1;
+                        }
                         LexItem::Paren(v) => match *v {
                             OPEN_PAREN => grammar_items.push(GrammarItem::GroupStart),
                             CLOSED_PAREN => grammar_items.push(GrammarItem::GroupEnd),
                             _ => {
+                                let at = span_of(input, prefix);
                                 return Err(ParseError::UnexpectedInput {
                                     found: v.to_string(),
                                     expected: vec![OPEN_PAREN.into(), CLOSED_PAREN.into()],
-                                })
+                                    line,
+                                    span: at.start..at.end + v.len_utf8(),
+                                });
                             }
                         },
-                        LexItem::Word(v) => {
+                        LexItem::Word(_) => {
+                            let v = command.as_ref();
                             let mut meta = vec![];
                             let params;
-                            let content;
-
-                            if v.starts_with("param") {
-                                let value = v.split('[').collect::<Vec<_>>();
-                                match value.get(1) {
-                                    Some(&"in]") => meta.push("in".into()),
-                                    Some(&"out]") => meta.push("out".into()),
-                                    Some(&"in,out]") | Some(&"out,in]") => {
-                                        meta.push("in".into());
-                                        meta.push("out".into());
+                            let tag;
+                            // Whether the tag is the target of a Markdown link
+                            let mut is_link_target = false;
+
+                            // The number of words following the tag used by the notation, if
+                            // they aren't just its parameters (like the direction of a `@param`)
+                            let mut used_words = None;
+
+                            if v == "param" || v.starts_with("param[") {
+                                let (direction, direction_words, name) =
+                                    split_param(v, &following[1..]);
+
+                                if let Some(direction) = direction {
+                                    if let Some(direction) = parse_direction(&direction) {
+                                        meta = direction;
+                                    } else {
+                                        let error = ParseError::UnexpectedInput {
+                                            found: direction,
+                                            expected: vec![
+                                                "[in]".into(),
+                                                "[out]".into(),
+                                                "[in,out]".into(),
+                                            ],
+                                            line,
+                                            span: notation_span(
+                                                input,
+                                                prefix,
+                                                following,
+                                                1 + direction_words,
+                                            ),
+                                        };
+
+                                        let Some(warnings) = warnings.as_deref_mut() else {
+                                            return Err(error);
+                                        };
+
+                                        warnings.push(error);
+                                        param_iter_skip_count = 1;
+                                        push_text(&mut grammar_items, &format!("@{v}"));
+                                        continue;
                                     }
-                                    _ => match value.get(1) {
-                                        None => {}
-                                        Some(v) => {
-                                            return Err(ParseError::UnexpectedInput {
-                                                found: v.to_string(),
-                                                expected: vec!["in]".into(), "out]".into()],
-                                            })
-                                        }
-                                    },
                                 }
 
-                                params = match item.get(3) {
-                                    None => vec![],
-                                    Some(LexItem::Word(v)) => vec![v.into()],
-                                    Some(_) => vec![],
-                                };
-
-                                content = "param"
+                                params = name.into_iter().collect::<Vec<_>>();
+                                used_words = Some(direction_words + params.len());
+                                tag = Tag::Param;
                             } else {
-                                content = v;
+                                tag = Tag::from(v);
 
-                                params = match v.as_str() {
-                                    "a" | "b" | "c" | "p" | "emoji" | "e" | "em" | "def"
-                                    | "class" | "category" | "concept" | "enum" | "example"
-                                    | "extends" | "file" | "sa" | "see" | "retval"
-                                    | "exception" | "throw" | "throws" => match item.get(3) {
+                                params = match &tag {
+                                    Tag::Sa | Tag::See => {
+                                        let (references, words) = split_references(&following[1..]);
+                                        used_words = Some(words);
+                                        references
+                                    }
+                                    Tag::Addindex => {
+                                        let (text, words) = rest_of_line(&following[1..]);
+                                        used_words = Some(words);
+                                        text.into_iter().collect()
+                                    }
+                                    // The name of the group, and its title up to the end of the line
+                                    Tag::Defgroup | Tag::Addtogroup | Tag::Weakgroup => {
+                                        let (text, words) = rest_of_line(&following[1..]);
+                                        used_words = Some(words);
+                                        text.iter()
+                                            .flat_map(|text| text.splitn(2, ' '))
+                                            .map(String::from)
+                                            .collect()
+                                    }
+                                    // The names of the groups, up to the end of the line
+                                    Tag::Ingroup => {
+                                        let (text, words) = rest_of_line(&following[1..]);
+                                        used_words = Some(words);
+                                        text.iter()
+                                            .flat_map(|text| text.split_whitespace())
+                                            .map(String::from)
+                                            .collect()
+                                    }
+                                    // The target of a Markdown link, like `[text](@ref target)`
+                                    Tag::Ref
+                                        if matches!(
+                                            grammar_items.last(),
+                                            Some(GrammarItem::Text(text)) if text.ends_with("](")
+                                        ) =>
+                                    {
+                                        match following.get(2) {
+                                            Some(LexItem::Word(v)) => {
+                                                is_link_target = true;
+                                                vec![v.to_string()]
+                                            }
+                                            _ => vec![],
+                                        }
+                                    }
+                                    Tag::Qualifier => {
+                                        let (label, words) = split_label(&following[1..]);
+                                        used_words = Some(words);
+                                        label.into_iter().collect()
+                                    }
+                                    // The file of a diagram, and its quoted caption (if any)
+                                    Tag::Ref
+                                    | Tag::Subpage
+                                    | Tag::Dotfile
+                                    | Tag::Mscfile
+                                    | Tag::Diafile => {
+                                        let (params, words) = split_ref(&following[1..]);
+                                        used_words = Some(words);
+                                        params
+                                    }
+                                    tag if tag.takes_word() => match following.get(2) {
                                         None => vec![],
-                                        Some(LexItem::Word(v)) => vec![v.into()],
+                                        Some(LexItem::Word(v)) => vec![v.to_string()],
                                         Some(_) => vec![],
                                     },
+                                    // The language of the code, like `@code{.py}`
+                                    Tag::Code => match (following.get(1), following.get(2)) {
+                                        (
+                                            Some(LexItem::Paren(OPEN_PAREN)),
+                                            Some(LexItem::Word(v)),
+                                        ) if v.starts_with('.') => vec![v.to_string()],
+                                        _ => vec![],
+                                    },
                                     _ => vec![],
                                 };
                             }
 
-                            if params.is_empty() {
-                                param_iter_skip_count = 1;
-                            } else {
-                                param_iter_skip_count = 2;
+                            param_iter_skip_count = 1 + used_words.unwrap_or(params.len());
+                            let span =
+                                notation_span(input, prefix, following, param_iter_skip_count);
+                            in_code = match tag {
+                                Tag::Code | Tag::Verbatim => true,
+                                Tag::Endcode | Tag::Endverbatim => false,
+                                _ => in_code,
+                            };
+                            in_foreign_region = tag == Tag::Docbookonly;
+
+                            if is_link_target {
+                                let target = params.into_iter().next().unwrap_or_default();
+                                grammar_items.push(GrammarItem::LinkTarget { target, line });
+                                continue;
                             }
 
                             grammar_items.push(GrammarItem::Notation {
                                 meta,
                                 params,
-                                tag: content.into(),
+                                tag,
+                                line,
+                                span,
                             });
                         }
                         _ => {}
@@ -124,28 +530,33 @@ fn parse_items(input: Vec<LexItem>) -> Result<Vec<GrammarItem>, ParseError> {
                     continue;
                 }
 
-                if let Some(prev) = grammar_items.last_mut() {
-                    match prev {
-                        GrammarItem::Text(text) => *text += v,
-                        _ => grammar_items.push(GrammarItem::Text(v.into())),
-                    }
-                } else {
-                    grammar_items.push(GrammarItem::Text(v.into()));
-                }
+                push_text(&mut grammar_items, v);
             }
-            LexItem::Space => {
-                if let Some(prev) = grammar_items.last_mut() {
-                    match prev {
-                        GrammarItem::Text(text) => *text += " ",
-                        _ => grammar_items.push(GrammarItem::Text("".into())),
-                    }
-                } else {
-                    grammar_items.push(GrammarItem::Text(" ".into()))
+            LexItem::Url(v) => {
+                if param_iter_skip_count > 0 {
+                    param_iter_skip_count -= 1;
+                    continue;
                 }
+
+                push_text(&mut grammar_items, &autolink(v));
             }
+            LexItem::Space => match grammar_items.last_mut() {
+                // Spaces between the parameters of a notation
+                Some(GrammarItem::Text(_)) if param_iter_skip_count > 0 => {}
+                Some(GrammarItem::Text(text)) => *text += " ",
+                Some(_) if in_code => grammar_items.push(GrammarItem::Text(" ".into())),
+                Some(_) => grammar_items.push(GrammarItem::Text("".into())),
+                None => grammar_items.push(GrammarItem::Text(" ".into())),
+            },
             LexItem::NewLine => {
-                if let Some(GrammarItem::Text(text)) = grammar_items.last_mut() {
-                    *text += "\n"
+                line += 1;
+                match grammar_items.last_mut() {
+                    Some(GrammarItem::Text(text)) => *text += "\n",
+                    // Keeps the end of the line of a `@par` without a title, to tell them apart
+                    Some(GrammarItem::Notation { tag: Tag::Par, .. }) => {
+                        grammar_items.push(GrammarItem::Text("\n".into()))
+                    }
+                    _ => {}
                 }
             }
             LexItem::Paren(v) => {
@@ -159,6 +570,279 @@ fn parse_items(input: Vec<LexItem>) -> Result<Vec<GrammarItem>, ParseError> {
     Ok(grammar_items)
 }
 
+/// Renames the tag if it's found in `aliases`, keeping its direction (like `[in]`) if any.
+fn rename_tag<'a>(word: &'a str, aliases: &HashMap<String, String>) -> Cow<'a, str> {
+    let (name, direction) = word.split_at(word.find('[').unwrap_or(word.len()));
+    match aliases.get(name) {
+        Some(alias) => Cow::Owned(format!("{alias}{direction}")),
+        None => Cow::Borrowed(word),
+    }
+}
+
+/// Returns the span of a notation, from its `@` up to the last of the `words` following it (the
+/// first one being the name of the command), including the brace closing its attributes (like the
+/// one of `@code{.py}`).
+fn notation_span(input: &str, at: &str, following: &[LexItem], words: usize) -> Range<usize> {
+    let start = span_of(input, at).start;
+    let mut end = start + at.len();
+    let mut remaining = words;
+    let mut in_braces = false;
+
+    let mut items = following.iter();
+    for item in items.by_ref() {
+        match item {
+            LexItem::Word(word) | LexItem::Url(word) => {
+                end = span_of(input, word).end;
+                remaining = remaining.saturating_sub(1);
+            }
+            LexItem::Paren(OPEN_PAREN) => in_braces = true,
+            _ => {}
+        }
+
+        if remaining == 0 {
+            break;
+        }
+    }
+
+    if in_braces && items.next() == Some(&LexItem::Paren(CLOSED_PAREN)) {
+        end += CLOSED_PAREN.len_utf8();
+    }
+
+    start..end
+}
+
+/// Whether the word following an `@` is a command, like `param[in]`.
+pub(crate) fn is_command(word: &str) -> bool {
+    let (name, rest) = word.split_at(word.find('[').unwrap_or(word.len()));
+
+    name.starts_with(|c: char| c.is_ascii_alphabetic())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && (rest.is_empty() || rest.starts_with('['))
+}
+
+/// Splits the direction (like `[in, out]`) and the name of a `@param`, given the tag and the items
+/// following it. Returns the direction, the number of words used by the direction after the tag,
+/// and the name.
+fn split_param(tag: &str, following: &[LexItem]) -> (Option<String>, usize, Option<String>) {
+    let mut words = following
+        .iter()
+        .filter(|item| !matches!(item, LexItem::Space))
+        .map_while(|item| match item {
+            LexItem::Word(word) => Some(*word),
+            _ => None,
+        })
+        .peekable();
+
+    let mut used_words = 0;
+    let mut direction = tag
+        .strip_prefix("param")
+        .filter(|direction| !direction.is_empty())
+        .map(String::from);
+
+    if direction.is_none() && words.peek().is_some_and(|word| word.starts_with('[')) {
+        direction = words.next().map(String::from);
+        used_words += 1;
+    }
+
+    if let Some(direction) = direction.as_mut() {
+        // Only the appended words are searched for the end, not the whole direction again
+        let mut closed = direction.contains(']');
+        while !closed {
+            let Some(word) = words.next() else {
+                break;
+            };
+
+            *direction += word;
+            used_words += 1;
+            closed = word.contains(']');
+        }
+    }
+
+    (direction, used_words, words.next().map(String::from))
+}
+
+/// Returns the words up to the end of the line given the items following a tag, like the text of
+/// `@addindex`, and the number of words.
+fn rest_of_line(following: &[LexItem]) -> (Option<String>, usize) {
+    let words = following
+        .iter()
+        .take_while(|item| !matches!(item, LexItem::NewLine | LexItem::At(_)))
+        .filter_map(|item| match item {
+            LexItem::Word(word) | LexItem::Url(word) => Some(*word),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    (
+        Some(words.join(" ")).filter(|text| !text.is_empty()),
+        words.len(),
+    )
+}
+
+/// Splits the references of a `@see` given the items following it, which are the words up to the
+/// end of the line, separated by spaces or commas. The argument lists of the references (like
+/// `(int, char*)` or `<T>`) are kept whole. Returns the references and the number of words used.
+fn split_references(following: &[LexItem]) -> (Vec<String>, usize) {
+    let words = following
+        .iter()
+        .skip_while(|item| matches!(item, LexItem::Space))
+        .take_while(|item| !matches!(item, LexItem::NewLine | LexItem::At(_) | LexItem::Paren(_)))
+        .filter_map(|item| match item {
+            LexItem::Word(word) | LexItem::Url(word) => Some(*word),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    let mut references = vec![];
+    let mut reference = String::new();
+    let mut depth = 0usize;
+
+    for word in &words {
+        if depth > 0 {
+            reference.push(' ');
+        }
+
+        for c in word.chars() {
+            match c {
+                '(' | '<' => depth += 1,
+                ')' | '>' => depth = depth.saturating_sub(1),
+                ',' if depth == 0 => {
+                    references
+                        .extend(Some(std::mem::take(&mut reference)).filter(|r| !r.is_empty()));
+                    continue;
+                }
+                _ => {}
+            }
+
+            reference.push(c);
+        }
+
+        if depth == 0 && !reference.is_empty() {
+            references.push(std::mem::take(&mut reference));
+        }
+    }
+
+    if !reference.is_empty() {
+        references.push(reference);
+    }
+
+    (references, words.len())
+}
+
+/// Splits the target of a `@ref` (or `@subpage`) and its quoted text (if any), like
+/// `label "The title".`, given the items following it. Returns the target, the text and the
+/// punctuation following the text (like the period), with the number of words used.
+fn split_ref(following: &[LexItem]) -> (Vec<String>, usize) {
+    let mut words = following
+        .iter()
+        .filter(|item| !matches!(item, LexItem::Space))
+        .map_while(|item| match item {
+            LexItem::Word(word) => Some(*word),
+            _ => None,
+        });
+
+    let Some(target) = words.next() else {
+        return (vec![], 0);
+    };
+
+    let mut text = String::new();
+    for (index, word) in words.enumerate() {
+        let word = match index {
+            0 => match word.strip_prefix('"') {
+                Some(word) => word,
+                None => break,
+            },
+            _ => {
+                text.push(' ');
+                word
+            }
+        };
+
+        if let Some(end) = word.find('"') {
+            text += &word[..end];
+            let trailing = &word[end + 1..];
+            return (vec![target.into(), text, trailing.into()], index + 2);
+        }
+
+        text += word;
+    }
+
+    (vec![target.into()], 1)
+}
+
+/// Splits the label of a `@qualifier`, either a word or a quoted text (like `"(optional)"`), given
+/// the items following it. Returns the label (without the quotes) with the number of words used.
+fn split_label(following: &[LexItem]) -> (Option<String>, usize) {
+    let mut words = following
+        .iter()
+        .filter(|item| !matches!(item, LexItem::Space))
+        .map_while(|item| match item {
+            LexItem::Word(word) => Some(*word),
+            _ => None,
+        });
+
+    let Some(first) = words.next() else {
+        return (None, 0);
+    };
+    let Some(quoted) = first.strip_prefix('"') else {
+        return (Some(first.into()), 1);
+    };
+
+    let mut label = String::new();
+    for (index, word) in [quoted].into_iter().chain(words).enumerate() {
+        if index > 0 {
+            label.push(' ');
+        }
+
+        if let Some(end) = word.find('"') {
+            label += &word[..end];
+            return (Some(label), index + 1);
+        }
+        label += word;
+    }
+
+    // An unterminated quote, where only the first word is the label
+    (Some(first.into()), 1)
+}
+
+/// Parses a direction like `[in]` or `[ IN, out ]`, ignoring whitespace and case.
+fn parse_direction(direction: &str) -> Option<Vec<String>> {
+    let inner = direction.strip_prefix('[')?.strip_suffix(']')?;
+    let (mut is_in, mut is_out) = (false, false);
+
+    for part in inner.split(',') {
+        match part.trim().to_lowercase().as_str() {
+            "in" => is_in = true,
+            "out" => is_out = true,
+            _ => return None,
+        }
+    }
+
+    Some(match (is_in, is_out) {
+        (true, true) => vec!["in".into(), "out".into()],
+        (true, false) => vec!["in".into()],
+        _ => vec!["out".into()],
+    })
+}
+
+/// Renders a URL as a Markdown autolink. URLs without a scheme, like `www.example.com`, are
+/// linked with `http://`, as Doxygen does.
+pub(crate) fn autolink(url: &str) -> String {
+    if url.starts_with("www.") {
+        format!("[{url}](http://{url})")
+    } else {
+        format!("<{url}>")
+    }
+}
+
+fn push_text(grammar_items: &mut Vec<GrammarItem>, value: &str) {
+    if let Some(GrammarItem::Text(text)) = grammar_items.last_mut() {
+        *text += value;
+    } else {
+        grammar_items.push(GrammarItem::Text(value.into()));
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -173,6 +857,8 @@ mod test {
                     meta: vec![],
                     params: vec![],
                     tag: "name".into(),
+                    line: 1,
+                    span: 0..5,
                 },
                 GrammarItem::Text("Memory Management".into())
             ]
@@ -189,6 +875,8 @@ mod test {
                     meta: vec![],
                     params: vec![],
                     tag: "note".into(),
+                    line: 1,
+                    span: 0..5,
                 },
                 GrammarItem::Text("hoge_t = {a, b, c}".into())
             ]
@@ -206,12 +894,80 @@ mod test {
                     meta: vec!["in".into()],
                     params: vec!["random".into()],
                     tag: "param".into(),
+                    line: 1,
+                    span: 0..17,
                 },
                 GrammarItem::Text(" This is, without a doubt, a random argument.".into())
             ]
         );
     }
 
+    #[test]
+    pub fn recovers_from_invalid_direction() {
+        let mut warnings = vec![];
+        let result = parse_recovering(
+            "@param[up] random Random.",
+            &Options::default(),
+            Some(&mut warnings),
+        )
+        .unwrap();
+        assert_eq!(
+            result,
+            vec![GrammarItem::Text("@param[up] random Random.".into())]
+        );
+        assert_eq!(warnings.len(), 1);
+
+        assert!(parse("@param[up] random Random.".into()).is_err());
+    }
+
+    #[test]
+    pub fn param_direction_variations() {
+        for input in [
+            "@param [in] random Random.",
+            "@param[ in ] random Random.",
+            "@param[IN] random Random.",
+            "@param [ In ]   random Random.",
+        ] {
+            assert_eq!(
+                parse(input.into()).unwrap(),
+                vec![
+                    GrammarItem::Notation {
+                        meta: vec!["in".into()],
+                        params: vec!["random".into()],
+                        tag: "param".into(),
+                        line: 1,
+                        span: 0..input.len() - " Random.".len(),
+                    },
+                    GrammarItem::Text(" Random.".into())
+                ]
+            );
+        }
+
+        let result = parse("@param[ out , in ] random Random.".into()).unwrap();
+        assert_eq!(
+            result,
+            vec![
+                GrammarItem::Notation {
+                    meta: vec!["in".into(), "out".into()],
+                    params: vec!["random".into()],
+                    tag: "param".into(),
+                    line: 1,
+                    span: 0..25,
+                },
+                GrammarItem::Text(" Random.".into())
+            ]
+        );
+    }
+
+    #[test]
+    pub fn not_commands() {
+        let result = parse("Mail @example.com, or @3.".into()).unwrap();
+        assert_eq!(
+            result,
+            vec![GrammarItem::Text("Mail @example.com, or @3.".into())]
+        );
+    }
+
     #[test]
     pub fn groups() {
         let result = parse("@{\n* @name Memory Management\n@}".into()).unwrap();
@@ -224,6 +980,8 @@ mod test {
                     meta: vec![],
                     params: vec![],
                     tag: "name".into(),
+                    line: 2,
+                    span: 5..10,
                 },
                 GrammarItem::Text("Memory Management\n".into()),
                 GrammarItem::GroupEnd
@@ -231,9 +989,38 @@ mod test {
         );
     }
 
+    #[test]
+    pub fn code_language() {
+        let result = parse("@code{.py}\npass\n@endcode".into()).unwrap();
+        assert_eq!(
+            result,
+            vec![
+                GrammarItem::Notation {
+                    meta: vec![],
+                    params: vec![".py".into()],
+                    tag: "code".into(),
+                    line: 1,
+                    span: 0..10,
+                },
+                GrammarItem::Text("pass\n".into()),
+                GrammarItem::Notation {
+                    meta: vec![],
+                    params: vec![],
+                    tag: "endcode".into(),
+                    line: 3,
+                    span: 16..24,
+                },
+            ]
+        );
+    }
+
     #[test]
     pub fn trims_param_texts() {
-        let result = parse("@param[in]           var                                         Example description".into()).unwrap();
+        let result = parse(
+            "@param[in]           var                                         Example description"
+                .into(),
+        )
+        .unwrap();
         assert_eq!(
             result,
             vec![
@@ -241,9 +1028,36 @@ mod test {
                     meta: vec!["in".into()],
                     params: vec!["var".into()],
                     tag: "param".into(),
+                    line: 1,
+                    span: 0..24,
                 },
                 GrammarItem::Text(" Example description".into())
             ]
         )
     }
+
+    #[test]
+    fn displays_errors() {
+        let error = parse("Text\n@param[sideways] a".into()).unwrap_err();
+
+        assert_eq!(
+            error,
+            ParseError::UnexpectedInput {
+                found: "[sideways]".into(),
+                expected: vec!["[in]".into(), "[out]".into(), "[in,out]".into()],
+                line: 2,
+                span: 5..21,
+            }
+        );
+        assert_eq!(error.code(), "E002");
+        assert_eq!((error.line(), error.span()), (Some(2), Some(5..21)));
+        assert_eq!(
+            error.to_string(),
+            "line 2: found `[sideways]`, expected one of `[in]`, `[out]`, `[in,out]`"
+        );
+        assert_eq!(
+            ParseError::WriteFailed.to_string(),
+            "failed to write the output"
+        );
+    }
 }
@@ -0,0 +1,413 @@
+//! Translation of Doxygen to reStructuredText, for the documentation built with Sphinx.
+//!
+//! The Doxygen is parsed and split into sections like for the Rustdoc (see [`RustdocComment`]),
+//! and the sections are rendered with the reStructuredText markup: the parameters, the returned
+//! value and the exceptions become the field lists of Sphinx (like `:param name:`), and the notes
+//! and warnings become directives.
+
+use crate::comment::{convert_with_options, Direction, Param, ReturnValue, RustdocComment, Throws};
+use crate::generator::ParseError;
+use crate::markup::{self, Block, Inline, Sections};
+use crate::options::Options;
+use crate::tag::Tag;
+
+/// The characters underlining the headings, by level.
+const UNDERLINES: [char; 5] = ['=', '-', '~', '^', '"'];
+
+/// The indentation of the content of the directives and fields.
+const INDENT: &str = "   ";
+
+/// Translates a Doxygen comment to reStructuredText.
+///
+/// # Examples
+///
+/// ```
+/// use doxygen_rs::rst::rst;
+///
+/// let input = "@brief Frees a @c buffer\n@param[in] buffer The buffer.\n@note Thread-safe.";
+///
+/// assert_eq!(
+///     rst(input.into()).unwrap(),
+///     "Frees a ``buffer``\n\n.. note:: Thread-safe.\n\n:param buffer: (in) The buffer."
+/// );
+/// ```
+///
+/// # Errors
+///
+/// See [`crate::generator::rustdoc`].
+pub fn rst(input: String) -> Result<String, ParseError> {
+    rst_with_options(input, &Options::default())
+}
+
+/// Translates a Doxygen comment to reStructuredText, using the given [`Options`].
+///
+/// # Errors
+///
+/// See [`crate::generator::rustdoc_with_options`].
+pub fn rst_with_options(input: String, options: &Options) -> Result<String, ParseError> {
+    Ok(render(&convert_with_options(input, options)?, options))
+}
+
+/// Renders the sections of a translated comment as reStructuredText, in the order of
+/// [`Options::section_order`] like the Rustdoc.
+///
+/// The headings (like the one of the `# Safety` section) are underlined with `=` for the default
+/// [`Options::heading_level`], then `-`, `~`, `^` and `"` for the nested ones.
+pub fn render(comment: &RustdocComment, options: &Options) -> String {
+    markup::render(comment, options, &Rst)
+}
+
+/// Creates a heading of the given Markdown level, underlined with the character of its level
+/// relative to [`Options::heading_level`].
+fn heading(title: &str, level: usize, options: &Options) -> String {
+    let depth = level.saturating_sub(options.heading_level);
+    let underline = UNDERLINES[depth.min(UNDERLINES.len() - 1)];
+
+    format!(
+        "{title}\n{}",
+        underline.to_string().repeat(title.chars().count())
+    )
+}
+
+/// Creates a directive, like `.. note:: Text.`, indenting the lines of its content after the
+/// first one.
+fn directive(name: &str, content: &str) -> String {
+    format!(".. {name}:: {}", indent_body(content))
+}
+
+/// Creates a field, like `:param name: Text.`, indenting the lines of its body after the first
+/// one. A body starting with a line break starts on the line following the name.
+fn field(name: &str, body: &str) -> String {
+    match body.is_empty() {
+        true => format!(":{name}:"),
+        false => format!(":{name}: {}", indent_body(body)),
+    }
+    .replace(": \n", ":\n")
+}
+
+fn indent_body(body: &str) -> String {
+    body.split('\n')
+        .enumerate()
+        .map(|(index, line)| match index == 0 || line.is_empty() {
+            true => line.to_string(),
+            false => format!("{INDENT}{line}"),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn list(items: &[String], options: &Options) -> String {
+    items
+        .iter()
+        .map(|item| format!("* {}", indent_body(&markdown(item, options))))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Creates an inline literal, like ` ``example`` `.
+fn code(text: &str) -> String {
+    format!("``{text}``")
+}
+
+/// The kind of the last block translated by [`markdown`], as reStructuredText needs blank lines
+/// between the blocks of different kinds, like before a nested list.
+#[derive(Clone, Copy, Eq, PartialEq)]
+enum Kind {
+    Blank,
+    Line,
+    ListItem(usize),
+    Other,
+}
+
+/// Translates the Markdown of the Rustdoc to reStructuredText.
+fn markdown(text: &str, options: &Options) -> String {
+    let mut result: Vec<String> = vec![];
+    let mut previous = Kind::Blank;
+    // The widths of the markers of the lists the current item is nested in
+    let mut marker_widths: Vec<usize> = vec![];
+
+    for block in markup::blocks(text) {
+        let (kind, block) = match block {
+            Block::Heading { level, text } => (Kind::Other, heading(&inline(text), level, options)),
+            Block::Code { language, lines } => {
+                let code = lines
+                    .iter()
+                    .map(|line| match line.is_empty() {
+                        true => String::new(),
+                        false => format!("{INDENT}{line}"),
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                let directive = match language {
+                    "" | "text" => "::".to_string(),
+                    language => format!(".. code-block:: {language}"),
+                };
+                (Kind::Other, format!("{directive}\n\n{code}"))
+            }
+            Block::Quote(quote) => {
+                let quote = markdown(&quote, options);
+                (Kind::Other, format!("{INDENT}{}", indent_body(&quote)))
+            }
+            Block::ListItem {
+                ordered,
+                depth,
+                text,
+            } => {
+                let marker = match ordered {
+                    true => "#.",
+                    false => "*",
+                };
+                marker_widths.truncate(depth);
+                let indent = " ".repeat(marker_widths.iter().sum());
+                marker_widths.push(marker.len() + 1);
+                (
+                    Kind::ListItem(depth),
+                    format!("{indent}{marker} {}", inline(text)),
+                )
+            }
+            // The lines following a list item are the rest of its text
+            Block::Line(line) if matches!(previous, Kind::ListItem(_)) => {
+                let indent = " ".repeat(marker_widths.iter().sum());
+                result.push(format!("{indent}{}", inline(line)));
+                continue;
+            }
+            Block::Line(line) => (Kind::Line, inline(line)),
+            Block::Blank => {
+                marker_widths.clear();
+                (Kind::Blank, String::new())
+            }
+        };
+
+        let separated = kind == Kind::Blank
+            || previous == Kind::Blank
+            || (kind == previous && kind != Kind::Other);
+        if !separated {
+            result.push(String::new());
+        }
+
+        result.push(block);
+        previous = kind;
+    }
+
+    result.join("\n").trim().to_string()
+}
+
+fn inline(text: &str) -> String {
+    markup::inline(text, &Rst)
+}
+
+/// The sections of a comment in reStructuredText, with the field lists of Sphinx.
+impl Sections for Rst {
+    fn markdown(&self, text: &str, options: &Options) -> String {
+        markdown(text, options)
+    }
+
+    fn admonition(&self, tag: &Tag, text: &str, options: &Options) -> String {
+        let name = match tag {
+            Tag::Note => "note",
+            Tag::Warning => "warning",
+            Tag::Important => "important",
+            Tag::Deprecated => {
+                let message = format!("Deprecated. {}", markdown(text, options));
+                return directive("caution", message.trim_end());
+            }
+            _ => return directive("versionadded", &inline(text)),
+        };
+
+        directive(name, &markdown(text, options))
+    }
+
+    /// Creates a rubric, like `.. rubric:: Preconditions`, followed by the items.
+    fn titled_list(&self, title: &str, items: &[String], options: &Options) -> String {
+        match items.is_empty() {
+            true => String::new(),
+            false => format!(".. rubric:: {title}\n\n{}", list(items, options)),
+        }
+    }
+
+    fn arguments(&self, params: &[Param], _: &Options) -> String {
+        let fields = params.iter().map(|param| {
+            let direction = match param.direction {
+                Some(Direction::In) => "(in) ",
+                Some(Direction::Out) => "(out) ",
+                Some(Direction::InOut) => "(in, out) ",
+                None => "",
+            };
+            let description = inline(&param.description.replace('\n', " "));
+            field(
+                &format!("param {}", param.name),
+                format!("{direction}{description}").trim_end(),
+            )
+        });
+
+        fields.collect::<Vec<_>>().join("\n")
+    }
+
+    fn returns(&self, returns: Option<&str>, retvals: &[ReturnValue], options: &Options) -> String {
+        let mut body = returns
+            .map(|returns| markdown(returns, options))
+            .unwrap_or_default();
+        let retvals = retvals
+            .iter()
+            .map(|retval| match retval.description.is_empty() {
+                true => format!("* {}", code(&retval.value)),
+                false => format!(
+                    "* {} - {}",
+                    code(&retval.value),
+                    inline(&retval.description.replace('\n', " "))
+                ),
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        if !retvals.is_empty() {
+            body += match body.is_empty() {
+                true => "\n",
+                false => "\n\n",
+            };
+            body += &retvals;
+        }
+
+        match returns.is_some() || !retvals.is_empty() {
+            true => field("returns", &body),
+            false => String::new(),
+        }
+    }
+
+    fn errors(&self, throws: &[Throws], _: &Options) -> String {
+        let fields = throws.iter().map(|throws| {
+            let description = inline(&throws.description.replace('\n', " "));
+            field(&format!("raises {}", throws.name), &description)
+        });
+
+        fields.collect::<Vec<_>>().join("\n")
+    }
+
+    fn safety(&self, preconditions: &[String], options: &Options) -> String {
+        match preconditions.is_empty() {
+            true => String::new(),
+            false => format!(
+                "{}\n\n{}",
+                heading("Safety", options.heading_level, options),
+                list(preconditions, options)
+            ),
+        }
+    }
+
+    fn see_also(&self, targets: &[String], _: &Options) -> String {
+        match targets.is_empty() {
+            true => String::new(),
+            false => {
+                let targets = targets.iter().map(|target| code(target));
+                directive("seealso", &targets.collect::<Vec<_>>().join(", "))
+            }
+        }
+    }
+}
+
+/// The inline markup of reStructuredText (and Sphinx, for the references).
+struct Rst;
+
+impl Inline for Rst {
+    fn code(&self, code: &str) -> String {
+        self::code(code)
+    }
+
+    fn italics(&self, text: &str) -> String {
+        format!("*{text}*")
+    }
+
+    // The markup can't be nested, so the labels are left as plain text
+    fn link(&self, label: &str, url: &str) -> String {
+        format!("`{} <{url}>`__", label.replace(['`', '*'], ""))
+    }
+
+    fn reference(&self, label: &str, anchor: &str) -> String {
+        format!(":ref:`{} <{anchor}>`", label.replace(['`', '*'], ""))
+    }
+
+    fn autolink(&self, url: &str) -> String {
+        url.into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sections() {
+        let input = "@brief Reads a file.\n\nDetails with @b bold and @e italics.\n@sa read_all\n@param[out] buffer The buffer.\n@param size\n@returns The size.\n@retval -1 Failure.\n@throws io_error When it fails.\n@pre The file is open.\n@warning Slow.\n@deprecated Use @ref read_all.\n@since 1.2";
+
+        assert_eq!(
+            rst(input.into()).unwrap(),
+            "Reads a file.
+
+Details with **bold** and *italics*.
+
+.. warning:: Slow.
+
+.. rubric:: Preconditions
+
+* The file is open.
+
+.. caution:: Deprecated. Use :ref:`read_all <read_all>`.
+
+.. versionadded:: 1.2
+
+:param buffer: (out) The buffer.
+:param size:
+
+:returns: The size.
+
+   * ``-1`` - Failure.
+
+:raises io_error: When it fails.
+
+.. seealso:: ``read_all``"
+        );
+    }
+
+    #[test]
+    fn blocks() {
+        let input = "@brief Example\n\n# Usage\n- First\n  continued\n  - Nested\n- Second\n\n1. One\n2. Two\n@code{.c}\nint *x = NULL;\n@endcode\nSee [the `docs`](https://example.com) or https://example.com.";
+
+        assert_eq!(
+            rst(input.into()).unwrap(),
+            "Example
+
+Usage
+=====
+
+* First
+  continued
+
+  * Nested
+
+* Second
+
+#. One
+#. Two
+
+.. code-block:: c
+
+   int *x = NULL;
+
+See `the docs <https://example.com>`__ or https://example.com."
+        );
+    }
+
+    #[test]
+    fn safety() {
+        let options = Options {
+            pre_as_safety: true,
+            heading_level: 2,
+            ..Default::default()
+        };
+        let input = "@brief Frees memory.\n@retval 0 Success.\n@pre Valid.";
+
+        assert_eq!(
+            rst_with_options(input.into(), &options).unwrap(),
+            "Frees memory.\n\n:returns:\n   * ``0`` - Success.\n\nSafety\n======\n\n* Valid."
+        );
+    }
+}
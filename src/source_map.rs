@@ -0,0 +1,154 @@
+//! Mapping of the generated Rustdoc back to the Doxygen it was translated from.
+
+use std::ops::Range;
+
+/// The output lines attributed to a part of the input.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Mapping {
+    /// The lines of the Rustdoc, starting at 1 like the lines of
+    /// [`crate::generator::ParseError`].
+    pub output_lines: Range<usize>,
+    /// The bytes of the Doxygen.
+    pub input: Range<usize>,
+}
+
+/// Maps the lines of the generated Rustdoc to the bytes of the Doxygen input, so the warnings
+/// about the Rustdoc (like the ones of rustdoc itself) can be reported on the original comment.
+///
+/// The lines are attributed by finding their words in the input, which survives the reordering
+/// and rewrapping done by the generator. Lines made only of generated content (like the
+/// `# Arguments` heading or blank lines) aren't mapped.
+///
+/// # Examples
+///
+/// ```
+/// use doxygen_rs::generator::rustdoc_with_source_map;
+/// use doxygen_rs::options::Options;
+///
+/// let input = "Details.\n@brief Example\n@param a The value.";
+/// let (rustdoc, map) = rustdoc_with_source_map(input.into(), &Options::default()).unwrap();
+///
+/// assert_eq!(rustdoc.lines().nth(6), Some("* `a` - The value."));
+/// assert_eq!(&input[map.input_range(7).unwrap()], "a The value");
+/// assert_eq!(&input[map.input_range(1).unwrap()], "Example");
+/// ```
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct SourceMap {
+    /// The mappings, sorted by their output lines.
+    pub mappings: Vec<Mapping>,
+}
+
+impl SourceMap {
+    /// Creates the map of the Rustdoc `output` generated from the Doxygen `input`.
+    pub fn new(input: &str, output: &str) -> Self {
+        let input_words = words(input).collect::<Vec<_>>();
+        let mut mappings: Vec<Mapping> = vec![];
+        let mut cursor = 0;
+
+        for (index, line) in output.lines().enumerate() {
+            let line_words = words(line).map(|(_, word)| word).collect::<Vec<_>>();
+
+            // The generator mostly keeps the order of the input, so the search starts where the
+            // previous line ended, falling back to the start for the reordered sections
+            let Some((matched, end)) = find_words(&input_words, &line_words, cursor)
+                .or_else(|| find_words(&input_words, &line_words, 0))
+            else {
+                continue;
+            };
+            cursor = end;
+
+            let line = index + 1;
+            match mappings.last_mut() {
+                Some(last) if last.output_lines.end == line && last.input.end <= matched.start => {
+                    last.output_lines.end = line + 1;
+                    last.input.end = matched.end;
+                }
+                _ => mappings.push(Mapping {
+                    output_lines: line..line + 1,
+                    input: matched,
+                }),
+            }
+        }
+
+        Self { mappings }
+    }
+
+    /// Returns the bytes of the input the given output line (starting at 1) was generated from.
+    pub fn input_range(&self, output_line: usize) -> Option<Range<usize>> {
+        self.mappings
+            .iter()
+            .find(|mapping| mapping.output_lines.contains(&output_line))
+            .map(|mapping| mapping.input.clone())
+    }
+}
+
+/// Returns the alphanumeric words of the text, with their byte ranges.
+fn words(text: &str) -> impl Iterator<Item = (Range<usize>, &str)> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(move |word| {
+            let start = word.as_ptr() as usize - text.as_ptr() as usize;
+            (start..start + word.len(), word)
+        })
+}
+
+/// Finds the words of a line in order among the input words, starting at the input word `from`.
+/// Returns the byte range spanning the found words, and the index of the word after the last one.
+fn find_words(
+    input: &[(Range<usize>, &str)],
+    words: &[&str],
+    from: usize,
+) -> Option<(Range<usize>, usize)> {
+    let mut range: Option<Range<usize>> = None;
+    let mut position = from;
+
+    for word in words {
+        let Some(offset) = input
+            .get(position..)?
+            .iter()
+            .position(|(_, input_word)| input_word == word)
+        else {
+            continue;
+        };
+
+        let found = &input[position + offset].0;
+        position += offset + 1;
+        range = Some(match range {
+            Some(range) => range.start..found.end,
+            None => found.clone(),
+        });
+    }
+
+    range.map(|range| (range, position))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn maps_lines() {
+        let input = "@brief Example\n@param a The\n  value.\n@returns The result.";
+        let output = "Example\n\n# Arguments\n\n* `a` - The value.\n\n# Returns\n\nThe result.";
+        let map = SourceMap::new(input, output);
+
+        assert_eq!(map.input_range(1), Some(7..14));
+        assert_eq!(map.input_range(3), None);
+        assert_eq!(&input[map.input_range(5).unwrap()], "a The\n  value");
+        assert_eq!(&input[map.input_range(9).unwrap()], "The result");
+    }
+
+    #[test]
+    fn merges_contiguous_lines() {
+        let input = "First line\nsecond line";
+        let map = SourceMap::new(input, input);
+
+        assert_eq!(
+            map.mappings,
+            vec![Mapping {
+                output_lines: 1..3,
+                input: 0..input.len(),
+            }]
+        );
+    }
+}
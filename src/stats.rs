@@ -0,0 +1,50 @@
+//! Statistics about the translated Doxygen.
+
+use std::collections::BTreeMap;
+
+/// Tag usage collected by [`crate::generator::rustdoc_with_stats`].
+///
+/// The same [`Stats`] can be passed to multiple calls to accumulate the usage of a whole set of
+/// comments, like all the comments in a bindgen output.
+///
+/// # Examples
+///
+/// ```
+/// use doxygen_rs::generator::rustdoc_with_stats;
+/// use doxygen_rs::options::Options;
+/// use doxygen_rs::stats::Stats;
+///
+/// let mut stats = Stats::default();
+/// let options = Options::default();
+///
+/// rustdoc_with_stats("@brief First @mytag".into(), &options, &mut stats).unwrap();
+/// rustdoc_with_stats("@brief Second".into(), &options, &mut stats).unwrap();
+///
+/// assert_eq!(stats.comments, 2);
+/// assert_eq!(stats.tags["brief"], 2);
+/// assert_eq!(stats.unknown_tags["mytag"], 1);
+/// ```
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Stats {
+    /// Number of times each tag was found, including the unknown ones.
+    pub tags: BTreeMap<String, usize>,
+    /// Number of times each tag that couldn't be translated was found.
+    pub unknown_tags: BTreeMap<String, usize>,
+    /// Number of comments translated successfully.
+    pub comments: usize,
+}
+
+impl Stats {
+    /// Adds the usage recorded in `other`.
+    pub(crate) fn merge(&mut self, other: &Stats) {
+        for (tag, count) in &other.tags {
+            *self.tags.entry(tag.clone()).or_default() += count;
+        }
+
+        for (tag, count) in &other.unknown_tags {
+            *self.unknown_tags.entry(tag.clone()).or_default() += count;
+        }
+
+        self.comments += other.comments;
+    }
+}
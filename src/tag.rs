@@ -0,0 +1,449 @@
+//! The Doxygen tags (also known as commands), like `@param`.
+
+use std::fmt;
+
+use crate::comment::Visibility;
+
+macro_rules! tags {
+    ($($(#[$attribute:meta])* $variant:ident => $name:literal,)*) => {
+        /// A Doxygen tag, like [`Tag::Param`] for `@param`.
+        ///
+        /// Each spelling of a tag has its own variant (like [`Tag::Return`] and [`Tag::Returns`]),
+        /// so the tag can be written back as it was. The tags that aren't translated are kept in
+        /// [`Tag::Unknown`]. More tags can be supported in minor versions, so matching on the tags
+        /// needs a wildcard arm.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use doxygen_rs::tag::Tag;
+        ///
+        /// assert_eq!(Tag::from("retval"), Tag::Retval);
+        /// assert_eq!(Tag::from("mytag"), Tag::Unknown("mytag".into()));
+        /// assert_eq!(Tag::Retval.to_string(), "retval");
+        /// ```
+        #[derive(Debug, Clone, Eq, PartialEq, Hash)]
+        #[non_exhaustive]
+        pub enum Tag {
+            $(
+                #[doc = concat!("`@", $name, "`")]
+                $(#[$attribute])*
+                $variant,
+            )*
+            /// A tag that isn't translated, like `@mytag`, without the `@`.
+            Unknown(String),
+        }
+
+        impl Tag {
+            /// Returns the name of the tag, without the `@`.
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $(Tag::$variant => $name,)*
+                    Tag::Unknown(name) => name,
+                }
+            }
+        }
+
+        /// The names of all the tags, without the `@`.
+        #[cfg(test)]
+        const NAMES: &[&str] = &[$($name,)*];
+
+        impl From<&str> for Tag {
+            fn from(name: &str) -> Self {
+                match name {
+                    $($name => Tag::$variant,)*
+                    _ => Tag::Unknown(name.into()),
+                }
+            }
+        }
+    };
+}
+
+tags! {
+    A => "a",
+    Addindex => "addindex",
+    Addtogroup => "addtogroup",
+    Anchor => "anchor",
+    Attention => "attention",
+    B => "b",
+    Brief => "brief",
+    C => "c",
+    Category => "category",
+    Class => "class",
+    Code => "code",
+    Concept => "concept",
+    Def => "def",
+    Defgroup => "defgroup",
+    Deprecated => "deprecated",
+    Details => "details",
+    Diafile => "diafile",
+    Docbookonly => "docbookonly",
+    Dotfile => "dotfile",
+    E => "e",
+    Em => "em",
+    Emoji => "emoji",
+    Endcode => "endcode",
+    Enddocbookonly => "enddocbookonly",
+    Endverbatim => "endverbatim",
+    Enum => "enum",
+    Example => "example",
+    Exception => "exception",
+    Extends => "extends",
+    File => "file",
+    Hideenumvalues => "hideenumvalues",
+    Hideinitializer => "hideinitializer",
+    Hideinlinesource => "hideinlinesource",
+    Hiderefby => "hiderefby",
+    Hiderefs => "hiderefs",
+    Important => "important",
+    Ingroup => "ingroup",
+    Mscfile => "mscfile",
+    Note => "note",
+    P => "p",
+    Package => "package",
+    Par => "par",
+    Param => "param",
+    Post => "post",
+    Pre => "pre",
+    Private => "private",
+    Privatesection => "privatesection",
+    Protected => "protected",
+    Protectedsection => "protectedsection",
+    Protocol => "protocol",
+    Public => "public",
+    Publicsection => "publicsection",
+    Qualifier => "qualifier",
+    Ref => "ref",
+    Remark => "remark",
+    Remarks => "remarks",
+    Result => "result",
+    Return => "return",
+    Returns => "returns",
+    Retval => "retval",
+    Sa => "sa",
+    See => "see",
+    Short => "short",
+    Showenumvalues => "showenumvalues",
+    Showinitializer => "showinitializer",
+    Showinlinesource => "showinlinesource",
+    Showrefby => "showrefby",
+    Showrefs => "showrefs",
+    Since => "since",
+    Subpage => "subpage",
+    Throw => "throw",
+    Throws => "throws",
+    Verbatim => "verbatim",
+    Warning => "warning",
+    Weakgroup => "weakgroup",
+}
+
+impl Tag {
+    /// Whether the tag is a block command, which starts a new paragraph. The text following a
+    /// block command (like the description of a `@param` or the content of a `@note`) extends up
+    /// to the next blank line or block command.
+    pub(crate) fn is_block_command(&self) -> bool {
+        matches!(
+            self,
+            Tag::Param
+                | Tag::Retval
+                | Tag::Returns
+                | Tag::Return
+                | Tag::Result
+                | Tag::Throw
+                | Tag::Throws
+                | Tag::Exception
+                | Tag::Note
+                | Tag::Important
+                | Tag::Warning
+                | Tag::Attention
+                | Tag::Since
+                | Tag::Deprecated
+                | Tag::Remark
+                | Tag::Remarks
+                | Tag::Par
+                | Tag::Details
+                | Tag::Pre
+                | Tag::Post
+                | Tag::Brief
+                | Tag::Short
+                | Tag::Code
+                | Tag::Verbatim
+                | Tag::Dotfile
+                | Tag::Mscfile
+                | Tag::Diafile
+        ) || self.is_structural()
+    }
+
+    /// Whether the tag is a structural command, which only names the documented entity.
+    pub(crate) fn is_structural(&self) -> bool {
+        matches!(
+            self,
+            Tag::Def
+                | Tag::Class
+                | Tag::Category
+                | Tag::Concept
+                | Tag::Enum
+                | Tag::Example
+                | Tag::Extends
+                | Tag::File
+                | Tag::Package
+                | Tag::Protocol
+                | Tag::Defgroup
+                | Tag::Addtogroup
+                | Tag::Weakgroup
+                | Tag::Ingroup
+        )
+    }
+
+    /// Whether the tag takes the word following it as its parameter, like the name of `@retval`.
+    pub(crate) fn takes_word(&self) -> bool {
+        matches!(
+            self,
+            Tag::A
+                | Tag::Anchor
+                | Tag::B
+                | Tag::C
+                | Tag::P
+                | Tag::Emoji
+                | Tag::E
+                | Tag::Em
+                | Tag::Sa
+                | Tag::See
+                | Tag::Retval
+                | Tag::Exception
+                | Tag::Throw
+                | Tag::Throws
+        ) || self.is_structural()
+    }
+
+    /// Whether the tag starts or ends a region of markup for another output format, like
+    /// `@docbookonly`, which is left out of the Rustdoc.
+    pub(crate) fn is_foreign_region(&self) -> bool {
+        matches!(self, Tag::Docbookonly | Tag::Enddocbookonly)
+    }
+
+    /// Whether the tag controls what Doxygen shows along with the documentation, like
+    /// `@showenumvalues` or `@hiderefs`, which has no equivalent in Rustdoc.
+    pub(crate) fn is_display_control(&self) -> bool {
+        matches!(
+            self,
+            Tag::Showenumvalues
+                | Tag::Hideenumvalues
+                | Tag::Showrefs
+                | Tag::Hiderefs
+                | Tag::Showrefby
+                | Tag::Hiderefby
+                | Tag::Showinlinesource
+                | Tag::Hideinlinesource
+                | Tag::Showinitializer
+                | Tag::Hideinitializer
+        )
+    }
+
+    /// Returns the visibility set by the tag, like [`Visibility::Private`] for `@privatesection`.
+    pub(crate) fn visibility(&self) -> Option<Visibility> {
+        match self {
+            Tag::Public | Tag::Publicsection => Some(Visibility::Public),
+            Tag::Protected | Tag::Protectedsection => Some(Visibility::Protected),
+            Tag::Private | Tag::Privatesection => Some(Visibility::Private),
+            _ => None,
+        }
+    }
+}
+
+/// How a supported tag is rendered in the Rustdoc.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum TagOutput {
+    /// Styles the following word, like `@b` in bold.
+    Styled,
+    /// Replaced by an emoji, like `@emoji`.
+    Emoji,
+    /// A link, like `@ref`.
+    Link,
+    /// An entry of a generated section, like `@param` under `# Arguments`.
+    Section,
+    /// A block quote, like `@note`.
+    Quote,
+    /// A heading, like `@par`.
+    Heading,
+    /// A code block, like `@code`.
+    CodeBlock,
+    /// A paragraph of the description, like `@brief`.
+    Paragraph,
+    /// Nothing, like `@private` (exposed in [`crate::comment::RustdocComment`] instead), the
+    /// structural commands or `@docbookonly`.
+    Hidden,
+}
+
+/// A tag understood by the translation, see [`supported_tags`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct TagInfo {
+    /// The name of the tag, without the `@`.
+    pub name: &'static str,
+    /// The other names translated the same way, like `returns` for `return`.
+    pub aliases: &'static [&'static str],
+    /// Whether the tag takes an argument, like the name of a `@param`.
+    pub takes_argument: bool,
+    /// Whether the tag takes options in brackets or braces, like `@param[in]` or `@code{.c}`.
+    pub takes_meta: bool,
+    /// How the tag is rendered.
+    pub output: TagOutput,
+}
+
+const fn info(
+    name: &'static str,
+    aliases: &'static [&'static str],
+    takes_argument: bool,
+    takes_meta: bool,
+    output: TagOutput,
+) -> TagInfo {
+    TagInfo {
+        name,
+        aliases,
+        takes_argument,
+        takes_meta,
+        output,
+    }
+}
+
+static SUPPORTED_TAGS: &[TagInfo] = &[
+    info("a", &[], true, false, TagOutput::Styled),
+    info("addindex", &[], true, false, TagOutput::Hidden),
+    info("addtogroup", &[], true, false, TagOutput::Hidden),
+    info("anchor", &[], true, false, TagOutput::Link),
+    info("attention", &[], false, false, TagOutput::Quote),
+    info("b", &[], true, false, TagOutput::Styled),
+    info("brief", &["short"], false, false, TagOutput::Paragraph),
+    info("c", &[], true, false, TagOutput::Styled),
+    info("category", &[], true, false, TagOutput::Hidden),
+    info("class", &[], true, false, TagOutput::Hidden),
+    info("code", &[], false, true, TagOutput::CodeBlock),
+    info("concept", &[], true, false, TagOutput::Hidden),
+    info("def", &[], true, false, TagOutput::Hidden),
+    info("defgroup", &[], true, false, TagOutput::Hidden),
+    info("deprecated", &[], false, false, TagOutput::Quote),
+    info("details", &[], false, false, TagOutput::Paragraph),
+    info("diafile", &[], true, false, TagOutput::Link),
+    info("docbookonly", &[], false, false, TagOutput::Hidden),
+    info("dotfile", &[], true, false, TagOutput::CodeBlock),
+    info("e", &["em"], true, false, TagOutput::Styled),
+    info("emoji", &[], true, false, TagOutput::Emoji),
+    info("endcode", &[], false, false, TagOutput::CodeBlock),
+    info("enddocbookonly", &[], false, false, TagOutput::Hidden),
+    info("endverbatim", &[], false, false, TagOutput::CodeBlock),
+    info("enum", &[], true, false, TagOutput::Hidden),
+    info("example", &[], true, false, TagOutput::Hidden),
+    info("extends", &[], true, false, TagOutput::Hidden),
+    info("file", &[], true, false, TagOutput::Hidden),
+    info("hideenumvalues", &[], false, false, TagOutput::Hidden),
+    info("hideinitializer", &[], false, false, TagOutput::Hidden),
+    info("hideinlinesource", &[], false, false, TagOutput::Hidden),
+    info("hiderefby", &[], false, false, TagOutput::Hidden),
+    info("hiderefs", &[], false, false, TagOutput::Hidden),
+    info("important", &[], false, false, TagOutput::Quote),
+    info("ingroup", &[], true, false, TagOutput::Hidden),
+    info("mscfile", &[], true, false, TagOutput::CodeBlock),
+    info("note", &[], false, false, TagOutput::Quote),
+    info("p", &[], true, false, TagOutput::Styled),
+    info("package", &[], true, false, TagOutput::Hidden),
+    info("par", &[], true, false, TagOutput::Heading),
+    info("param", &[], true, true, TagOutput::Section),
+    info("post", &[], false, false, TagOutput::Paragraph),
+    info("pre", &[], false, false, TagOutput::Paragraph),
+    info("private", &[], false, false, TagOutput::Hidden),
+    info("privatesection", &[], false, false, TagOutput::Hidden),
+    info("protected", &[], false, false, TagOutput::Hidden),
+    info("protectedsection", &[], false, false, TagOutput::Hidden),
+    info("protocol", &[], true, false, TagOutput::Hidden),
+    info("public", &[], false, false, TagOutput::Hidden),
+    info("publicsection", &[], false, false, TagOutput::Hidden),
+    info("qualifier", &[], true, false, TagOutput::Hidden),
+    info("ref", &[], true, false, TagOutput::Link),
+    info("remark", &["remarks"], false, false, TagOutput::Quote),
+    info(
+        "return",
+        &["returns", "result"],
+        false,
+        false,
+        TagOutput::Section,
+    ),
+    info("retval", &[], true, false, TagOutput::Section),
+    info("see", &["sa"], true, false, TagOutput::Link),
+    info("showenumvalues", &[], false, false, TagOutput::Hidden),
+    info("showinitializer", &[], false, false, TagOutput::Hidden),
+    info("showinlinesource", &[], false, false, TagOutput::Hidden),
+    info("showrefby", &[], false, false, TagOutput::Hidden),
+    info("showrefs", &[], false, false, TagOutput::Hidden),
+    info("since", &[], false, false, TagOutput::Quote),
+    info("subpage", &[], true, false, TagOutput::Link),
+    info(
+        "throws",
+        &["throw", "exception"],
+        true,
+        false,
+        TagOutput::Section,
+    ),
+    info("verbatim", &[], false, false, TagOutput::CodeBlock),
+    info("warning", &[], false, false, TagOutput::Quote),
+    info("weakgroup", &[], true, false, TagOutput::Hidden),
+];
+
+/// Returns the tags understood by the translation, sorted by name, so tools like linters can
+/// validate the comments. The other tags are dropped (or kept or rejected, see
+/// [`crate::options::Options`]).
+///
+/// # Examples
+///
+/// ```
+/// use doxygen_rs::tag::{supported_tags, TagOutput};
+///
+/// let returns = supported_tags()
+///     .iter()
+///     .find(|info| info.name == "returns" || info.aliases.contains(&"returns"))
+///     .unwrap();
+/// assert_eq!(returns.name, "return");
+/// assert_eq!(returns.output, TagOutput::Section);
+/// ```
+pub fn supported_tags() -> &'static [TagInfo] {
+    SUPPORTED_TAGS
+}
+
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn supports_every_tag() {
+        let mut names = supported_tags()
+            .iter()
+            .flat_map(|info| [info.name].into_iter().chain(info.aliases.iter().copied()))
+            .collect::<Vec<_>>();
+        names.sort_unstable();
+
+        let mut expected = NAMES.to_vec();
+        expected.sort_unstable();
+        assert_eq!(names, expected);
+        assert!(supported_tags()
+            .windows(2)
+            .all(|pair| pair[0].name < pair[1].name));
+
+        for info in supported_tags() {
+            let tag = Tag::from(info.name);
+            assert_eq!(
+                info.output == TagOutput::Hidden,
+                tag.is_structural()
+                    || tag.visibility().is_some()
+                    || tag.is_foreign_region()
+                    || tag.is_display_control()
+                    || matches!(tag, Tag::Addindex | Tag::Qualifier),
+                "{tag}"
+            );
+        }
+    }
+}
@@ -0,0 +1,312 @@
+//! Doxygen tag files, to link to the documentation of other projects.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// The compounds whose members are named with their scope, like `Foo::bar`.
+const SCOPES: &[&str] = &[
+    "class",
+    "struct",
+    "union",
+    "interface",
+    "protocol",
+    "category",
+    "namespace",
+];
+
+/// The URLs of the symbols and anchors documented by another project, read from the tag file
+/// (`.tag`) generated by Doxygen with its `GENERATE_TAGFILE` option.
+///
+/// Given in [`Options::tag_files`](crate::options::Options::tag_files), the `@ref`, `@see` and
+/// `@sa` targets found in a tag file are linked to their documentation instead of being left as
+/// intra-doc links, which would be broken for items that aren't part of the crate.
+///
+/// # Examples
+///
+/// ```
+/// use doxygen_rs::generator::rustdoc_with_options;
+/// use doxygen_rs::options::Options;
+/// use doxygen_rs::tagfile::TagFile;
+///
+/// let xml = r#"
+/// <tagfile>
+///   <compound kind="class">
+///     <name>Foo</name>
+///     <filename>classFoo.html</filename>
+///     <member kind="function">
+///       <name>bar</name>
+///       <anchorfile>classFoo.html</anchorfile>
+///       <anchor>a1b2</anchor>
+///     </member>
+///   </compound>
+/// </tagfile>"#;
+///
+/// let options = Options {
+///     tag_files: vec![TagFile::parse(xml, "https://example.com/docs")],
+///     ..Default::default()
+/// };
+///
+/// let rustdoc = rustdoc_with_options("See @ref Foo::bar()".into(), &options).unwrap();
+/// assert_eq!(rustdoc, "See [`Foo::bar()`](https://example.com/docs/classFoo.html#a1b2)");
+/// ```
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct TagFile {
+    urls: HashMap<String, String>,
+}
+
+/// The compound being read, like a class or a file.
+#[derive(Default)]
+struct Compound {
+    kind: String,
+    name: String,
+    filename: String,
+}
+
+/// The member being read, like a function of a class.
+#[derive(Default)]
+struct Member {
+    name: String,
+    anchorfile: String,
+    anchor: String,
+}
+
+impl TagFile {
+    /// Reads the XML of a tag file, whose pages are found under `base_url` (like
+    /// `https://example.com/docs`).
+    ///
+    /// Malformed tag files aren't rejected: everything that could be read is kept.
+    pub fn parse(xml: &str, base_url: &str) -> Self {
+        let base_url = base_url.trim_end_matches('/');
+        let url = |file: &str, anchor: &str| {
+            let mut url = format!("{base_url}/{file}");
+            // Recent versions of Doxygen leave out the extension of the pages
+            if !file.contains('.') {
+                url += ".html";
+            }
+            if !anchor.is_empty() {
+                url += &format!("#{anchor}");
+            }
+            url
+        };
+
+        let mut urls = HashMap::new();
+        let mut compound: Option<Compound> = None;
+        let mut member: Option<Member> = None;
+        let mut docanchor_file = String::new();
+        let mut text = String::new();
+        let mut rest = xml;
+
+        while let Some(start) = rest.find('<') {
+            text += &rest[..start];
+            rest = &rest[start..];
+
+            let terminator = if rest.starts_with("<!--") {
+                "-->"
+            } else if rest.starts_with("<![CDATA[") {
+                "]]>"
+            } else {
+                ">"
+            };
+            let Some(end) = rest.find(terminator) else {
+                break;
+            };
+            let element = &rest[1..end];
+            rest = &rest[end + terminator.len()..];
+
+            if let Some(data) = element.strip_prefix("![CDATA[") {
+                text += data;
+                continue;
+            }
+            if element.starts_with(['!', '?']) {
+                continue;
+            }
+
+            let content = unescape(text.trim());
+            text.clear();
+
+            let Some(name) = element.strip_prefix('/') else {
+                let name = element.split_whitespace().next().unwrap_or_default();
+                match name {
+                    "compound" => {
+                        compound = Some(Compound {
+                            kind: attribute(element, "kind").unwrap_or_default(),
+                            ..Default::default()
+                        })
+                    }
+                    "member" => member = Some(Member::default()),
+                    "docanchor" => docanchor_file = attribute(element, "file").unwrap_or_default(),
+                    _ => {}
+                }
+                continue;
+            };
+
+            match (name.trim(), &mut member, &mut compound) {
+                ("name", Some(member), _) => member.name = content,
+                ("anchorfile", Some(member), _) => member.anchorfile = content,
+                ("anchor", Some(member), _) => member.anchor = content,
+                ("member", Some(finished), Some(compound)) => {
+                    let symbol = match SCOPES.contains(&compound.kind.as_str()) {
+                        true => format!("{}::{}", compound.name, finished.name),
+                        false => finished.name.clone(),
+                    };
+                    // The members of the groups are also listed in their files
+                    urls.entry(normalize(&symbol))
+                        .or_insert_with(|| url(&finished.anchorfile, &finished.anchor));
+                    member = None;
+                }
+                ("name", None, Some(compound)) => compound.name = content,
+                ("filename", None, Some(compound)) => compound.filename = content,
+                ("compound", _, Some(finished)) => {
+                    if !finished.filename.is_empty() {
+                        urls.entry(normalize(&finished.name))
+                            .or_insert_with(|| url(&finished.filename, ""));
+                    }
+                    compound = None;
+                }
+                ("docanchor", ..) => {
+                    urls.entry(content.clone())
+                        .or_insert_with(|| url(&docanchor_file, &content));
+                }
+                _ => {}
+            }
+        }
+
+        Self { urls }
+    }
+
+    /// Reads the tag file at `path`, whose pages are found under `base_url`. See
+    /// [`TagFile::parse`].
+    ///
+    /// # Errors
+    ///
+    /// This function errors if `path` can't be read.
+    pub fn load(path: impl AsRef<Path>, base_url: &str) -> io::Result<Self> {
+        Ok(Self::parse(&fs::read_to_string(path)?, base_url))
+    }
+
+    /// Returns the URL of the documentation of a symbol (like `Foo::bar`, `Foo#bar()` or
+    /// `foo_init`) or of an anchor.
+    pub fn url(&self, target: &str) -> Option<&str> {
+        self.urls.get(&normalize(target)).map(String::as_str)
+    }
+}
+
+/// Leaves out the arguments (like `(int)` or `<T>`) of a symbol, and uses `::` as the separator
+/// of its path, so `Foo<T>#bar(int)` is found as `Foo::bar`.
+pub(crate) fn normalize(symbol: &str) -> String {
+    let symbol = &symbol[..symbol.find('(').unwrap_or(symbol.len())];
+    let mut normalized = String::with_capacity(symbol.len());
+    let mut depth = 0usize;
+
+    for c in symbol.chars() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth = depth.saturating_sub(1),
+            '#' if depth == 0 => normalized += "::",
+            _ if depth == 0 => normalized.push(c),
+            _ => {}
+        }
+    }
+
+    normalized
+}
+
+/// Returns the value of an attribute of an element, like `kind` in `compound kind="class"`.
+fn attribute(element: &str, name: &str) -> Option<String> {
+    let start = element.find(&format!(" {name}="))? + name.len() + 2;
+    let quote = element[start..]
+        .chars()
+        .next()
+        .filter(|c| matches!(c, '"' | '\''))?;
+    let value = &element[start + 1..];
+    let end = value.find(quote)?;
+
+    Some(unescape(&value[..end]))
+}
+
+/// Replaces the XML entities, like `&lt;`, with their characters.
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TAG_FILE: &str = r#"<?xml version='1.0' encoding='UTF-8' standalone='yes' ?>
+<tagfile doxygen_version="1.9.8">
+  <compound kind="file">
+    <name>foo.h</name>
+    <path>include/</path>
+    <filename>foo_8h.html</filename>
+    <member kind="function">
+      <type>int</type>
+      <name>foo_init</name>
+      <anchorfile>foo_8h.html</anchorfile>
+      <anchor>a1</anchor>
+      <arglist>(void)</arglist>
+    </member>
+  </compound>
+  <compound kind="class">
+    <name>ns::Vec&lt;T&gt;</name>
+    <filename>classns_1_1Vec</filename>
+    <member kind="function">
+      <name>push</name>
+      <anchorfile>classns_1_1Vec</anchorfile>
+      <anchor>a2</anchor>
+    </member>
+  </compound>
+  <!-- <compound kind="page"><name>commented</name></compound> -->
+  <compound kind="page">
+    <name>intro</name>
+    <title>Introduction</title>
+    <filename>intro.html</filename>
+    <docanchor file="intro.html" title="Setup">setup</docanchor>
+  </compound>
+</tagfile>"#;
+
+    #[test]
+    fn urls() {
+        let tag_file = TagFile::parse(TAG_FILE, "https://example.com/");
+
+        assert_eq!(
+            tag_file.url("foo_init()"),
+            Some("https://example.com/foo_8h.html#a1")
+        );
+        assert_eq!(
+            tag_file.url("foo.h"),
+            Some("https://example.com/foo_8h.html")
+        );
+        assert_eq!(
+            tag_file.url("ns::Vec<T>"),
+            Some("https://example.com/classns_1_1Vec.html")
+        );
+        assert_eq!(
+            tag_file.url("ns::Vec<T>#push()"),
+            Some("https://example.com/classns_1_1Vec.html#a2")
+        );
+        assert_eq!(
+            tag_file.url("intro"),
+            Some("https://example.com/intro.html")
+        );
+        assert_eq!(
+            tag_file.url("setup"),
+            Some("https://example.com/intro.html#setup")
+        );
+        assert_eq!(tag_file.url("commented"), None);
+        assert_eq!(tag_file.url("push"), None);
+
+        // Malformed, with an unquoted attribute left out
+        let tag_file = TagFile::parse(
+            "<compound kind=éx><name>x</name><filename>x.html</filename></compound>",
+            "x",
+        );
+        assert_eq!(tag_file.url("x"), Some("x/x.html"));
+    }
+}
@@ -0,0 +1,779 @@
+//! Translation of the doc attributes of whole Rust files, like the bindings generated by bindgen.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use crate::comment::convert_with_options;
+use crate::diff;
+use crate::generator::{rustdoc_lossy_with_stats, ParseError};
+use crate::options::Options;
+use crate::stats::Stats;
+
+const DOC_ATTRIBUTE: &str = "#[doc = \"";
+
+/// The outcome of the translation of a file.
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    /// Tag usage of the translated comments.
+    pub stats: Stats,
+    /// The errors found in the comments, which are kept as their literal text. See
+    /// [`crate::generator::rustdoc_lossy`].
+    pub warnings: Vec<ParseError>,
+    /// The deprecated items, from `@deprecated`.
+    pub deprecations: Vec<Deprecation>,
+}
+
+/// A deprecated item found in a file, like a function of a header.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Deprecation {
+    /// The first line of the item following its doc attributes, like `pub fn open(path: *const
+    /// c_char) -> c_int;`, to tell which item is deprecated.
+    pub item_hint: String,
+    /// The deprecation message, translated to Rustdoc.
+    pub message: String,
+    /// The version the item is available since, from `@since`.
+    pub since: Option<String>,
+}
+
+/// Translates the doc attributes (like `#[doc = " @brief Example"]`) of the bindgen output at
+/// `path_in` to Rustdoc, writing the result to `path_out`. Meant to be called from build scripts,
+/// after generating the bindings.
+///
+/// # Examples
+///
+/// ```no_run
+/// use doxygen_rs::options::Options;
+/// use doxygen_rs::transform::transform_bindgen_output;
+///
+/// let out = std::env::var("OUT_DIR").unwrap();
+/// let bindings = format!("{out}/bindings.rs");
+///
+/// transform_bindgen_output(&bindings, &bindings, &Options::default()).unwrap();
+/// ```
+///
+/// # Errors
+///
+/// This function errors if `path_in` can't be read or `path_out` can't be written. The errors of
+/// the comments don't stop the translation, and are returned in the [`Report`] instead.
+pub fn transform_bindgen_output(
+    path_in: impl AsRef<Path>,
+    path_out: impl AsRef<Path>,
+    options: &Options,
+) -> io::Result<Report> {
+    let source = fs::read_to_string(path_in)?;
+    let (transformed, report) = transform_source(&source, options);
+    fs::write(path_out, transformed)?;
+
+    Ok(report)
+}
+
+/// Translates the doc attributes of the files under `root` matching the glob `pattern` (like
+/// `src/bindings/**/*.rs`, relative to `root`) in place, returning the [`Report`] of each file.
+///
+/// # Examples
+///
+/// ```no_run
+/// use doxygen_rs::options::Options;
+/// use doxygen_rs::transform::transform_directory;
+///
+/// let reports = transform_directory(".", "src/bindings/**/*.rs", &Options::default()).unwrap();
+///
+/// for (path, report) in reports {
+///     for warning in report.warnings {
+///         eprintln!("{}: {warning:?}", path.display());
+///     }
+/// }
+/// ```
+///
+/// # Errors
+///
+/// This function errors if the pattern is invalid, or if a file can't be read or written.
+pub fn transform_directory(
+    root: impl AsRef<Path>,
+    pattern: &str,
+    options: &Options,
+) -> io::Result<Vec<(PathBuf, Report)>> {
+    let mut reports = vec![];
+    let mut cache = Cache::new();
+
+    for path in find_files(root, pattern)? {
+        let source = fs::read_to_string(&path)?;
+        let (transformed, report) = transform_cached(&source, options, &mut cache);
+        if transformed != source {
+            fs::write(&path, transformed)?;
+        }
+
+        reports.push((path, report));
+    }
+
+    Ok(reports)
+}
+
+/// Translates the doc attributes of Rust source code to Rustdoc. Consecutive doc attributes (like
+/// the ones of the lines of a comment) are translated together, into a single attribute.
+///
+/// # Examples
+///
+/// ```
+/// use doxygen_rs::options::Options;
+/// use doxygen_rs::transform::transform_source;
+///
+/// let source = "#[doc = \" @brief Example\"]\n#[doc = \" @c code\"]\npub fn example() {}";
+/// let (transformed, report) = transform_source(source, &Options::default());
+///
+/// assert_eq!(transformed, "#[doc = \" Example\\n `code`\"]\npub fn example() {}");
+/// assert_eq!(report.stats.comments, 1);
+/// ```
+pub fn transform_source(source: &str, options: &Options) -> (String, Report) {
+    transform_cached(source, options, &mut Cache::new())
+}
+
+/// Like [`transform_source`], reusing the translations of `cache`.
+fn transform_cached(source: &str, options: &Options, cache: &mut Cache) -> (String, Report) {
+    let (edits, report) = find_edits(source, options, cache);
+    let mut result = String::with_capacity(source.len());
+    let mut position = 0;
+
+    for edit in edits {
+        result += &source[position..edit.range.start];
+        result += &edit.replacement;
+        position = edit.range.end;
+    }
+
+    result += &source[position..];
+
+    (result, report)
+}
+
+/// Translates the doc attributes of Rust source code like [`transform_source`], but returns the
+/// changes as a unified diff instead, labeling the file as `path`.
+///
+/// # Examples
+///
+/// ```
+/// use doxygen_rs::options::Options;
+/// use doxygen_rs::transform::diff_source;
+///
+/// let source = "#[doc = \" @b Example\"]\nstruct Example;\n";
+/// let (diff, _) = diff_source(source, "src/lib.rs", &Options::default());
+///
+/// assert_eq!(
+///     diff,
+///     "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1,2 +1,2 @@\n-#[doc = \" @b Example\"]\n+#[doc = \" **Example**\"]\n struct Example;\n"
+/// );
+/// ```
+pub fn diff_source(source: &str, path: &str, options: &Options) -> (String, Report) {
+    diff_cached(source, path, options, &mut Cache::new())
+}
+
+/// Like [`diff_source`], reusing the translations of `cache`.
+fn diff_cached(source: &str, path: &str, options: &Options, cache: &mut Cache) -> (String, Report) {
+    let (edits, report) = find_edits(source, options, cache);
+
+    (diff::unified_diff(path, source, &edits), report)
+}
+
+/// Translates the doc attributes of the files under `root` matching the glob `pattern` like
+/// [`transform_directory`], without writing anything. Returns the unified diff of all the files
+/// with the [`Report`] of each file, so the translation can be reviewed before applying it.
+///
+/// # Errors
+///
+/// This function errors if the pattern is invalid, or if a file can't be read.
+pub fn diff_directory(
+    root: impl AsRef<Path>,
+    pattern: &str,
+    options: &Options,
+) -> io::Result<(String, Vec<(PathBuf, Report)>)> {
+    let mut diff = String::new();
+    let mut reports = vec![];
+    let mut cache = Cache::new();
+
+    for path in find_files(root, pattern)? {
+        let source = fs::read_to_string(&path)?;
+        let path_name = path.to_string_lossy();
+        let (file_diff, report) = diff_cached(&source, &path_name, options, &mut cache);
+
+        diff += &file_diff;
+        reports.push((path, report));
+    }
+
+    Ok((diff, reports))
+}
+
+/// A replacement of a part of a source, like a translated comment.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Edit {
+    /// The bytes of the source to replace.
+    pub range: Range<usize>,
+    /// The text replacing them.
+    pub replacement: String,
+}
+
+/// Translates only the doc comment of Rust source code containing the byte `range` (like the
+/// selection or the cursor of an editor), returning the edit replacing it, for the assists of
+/// editors and language servers.
+///
+/// The doc comments are either line comments (`///` or `//!`, translated as a whole run of lines)
+/// or doc attributes (like `#[doc = " @brief Example"]`, see [`transform_source`]). Returns `None`
+/// if there's no doc comment containing the range, or if the translation doesn't change it.
+///
+/// # Examples
+///
+/// ```
+/// use doxygen_rs::transform::convert_range;
+///
+/// let source = "    /// @brief Example\n    /// @c code\n    fn example() {}";
+/// let edit = convert_range(source, 10..10).unwrap();
+///
+/// assert_eq!(edit.range, 4..38);
+/// assert_eq!(edit.replacement, "/// Example\n    /// `code`");
+/// assert_eq!(convert_range(source, 40..40), None);
+/// ```
+pub fn convert_range(source: &str, range: Range<usize>) -> Option<Edit> {
+    convert_range_with_options(source, range, &Options::default())
+}
+
+/// Translates only the doc comment of Rust source code containing the byte `range` like
+/// [`convert_range`], using the given [`Options`].
+pub fn convert_range_with_options(
+    source: &str,
+    range: Range<usize>,
+    options: &Options,
+) -> Option<Edit> {
+    let contains = |edit: &Edit| edit.range.start <= range.start && range.end <= edit.range.end;
+
+    if let Some((comment, marker)) = line_comment(source, range.clone()) {
+        let doxygen = source[comment.clone()]
+            .lines()
+            .map(|line| line.trim_start()[marker.len()..].to_string())
+            .map(|line| line.strip_prefix(' ').map(String::from).unwrap_or(line))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let (rustdoc, warnings) = rustdoc_lossy_with_stats(doxygen, options, &mut Stats::default());
+
+        // The comments exceeding the limits are left as they are, like in `transform_source`
+        if warnings
+            .iter()
+            .any(|warning| matches!(warning, ParseError::LimitExceeded { .. }))
+        {
+            return None;
+        }
+
+        let line_start = source[..comment.start]
+            .rfind('\n')
+            .map_or(0, |index| index + 1);
+        let indent = &source[line_start..comment.start];
+        let replacement = rustdoc
+            .split('\n')
+            .map(|line| format!("{marker}{}", doc_line(line)))
+            .collect::<Vec<_>>()
+            .join(&format!("\n{indent}"));
+
+        return (replacement != source[comment.clone()]).then_some(Edit {
+            range: comment,
+            replacement,
+        });
+    }
+
+    let (edits, _) = find_edits(source, options, &mut Cache::new());
+    edits.into_iter().find(contains)
+}
+
+/// Finds the run of doc comment lines (`///` or `//!`) containing the byte range, returning its
+/// bytes (from the first marker to the end of the last line) and its marker.
+fn line_comment(source: &str, range: Range<usize>) -> Option<(Range<usize>, &'static str)> {
+    let mut lines = vec![];
+    let mut offset = 0;
+    for line in source.split_inclusive('\n') {
+        lines.push(offset..offset + line.trim_end_matches(['\n', '\r']).len());
+        offset += line.len();
+    }
+
+    let marker_of = |line: &Range<usize>| {
+        let content = source[line.clone()].trim_start();
+        ["///", "//!"]
+            .into_iter()
+            .find(|marker| content.starts_with(marker) && !content.starts_with("////"))
+    };
+
+    let index = lines
+        .iter()
+        .position(|line| line.start <= range.start && range.start <= line.end)?;
+    let marker = marker_of(&lines[index])?;
+
+    let first = (0..index)
+        .rev()
+        .take_while(|&other| marker_of(&lines[other]) == Some(marker))
+        .last()
+        .unwrap_or(index);
+    let last = (index + 1..lines.len())
+        .take_while(|&other| marker_of(&lines[other]) == Some(marker))
+        .last()
+        .unwrap_or(index);
+
+    let first_line = &source[lines[first].clone()];
+    let start = lines[first].start + first_line.len() - first_line.trim_start().len();
+    let end = lines[last].end;
+
+    (start <= range.start && range.end <= end).then_some((start..end, marker))
+}
+
+/// The translations of the comments, by Doxygen. Only filled if [`Options::cache_comments`] is
+/// enabled.
+type Cache = HashMap<String, Translation>;
+
+/// The translation of a comment.
+#[derive(Clone)]
+struct Translation {
+    /// The doc attribute of the Rustdoc, or `None` if the comment exceeds the limits and is left
+    /// as it is.
+    attribute: Option<String>,
+    warnings: Vec<ParseError>,
+    stats: Stats,
+}
+
+impl Translation {
+    fn new(doxygen: String, options: &Options) -> Self {
+        let mut stats = Stats::default();
+        let (rustdoc, warnings) = rustdoc_lossy_with_stats(doxygen, options, &mut stats);
+
+        // The comments exceeding the limits are left as they are
+        let failed = warnings
+            .iter()
+            .any(|warning| matches!(warning, ParseError::LimitExceeded { .. }));
+
+        Self {
+            attribute: (!failed).then(|| doc_attribute(&rustdoc)),
+            warnings,
+            stats,
+        }
+    }
+}
+
+/// Finds the doc attributes of the source that change once translated.
+fn find_edits(source: &str, options: &Options, cache: &mut Cache) -> (Vec<Edit>, Report) {
+    let mut report = Report::default();
+    let mut edits = vec![];
+    let mut position = 0;
+
+    while let Some(found) = source[position..].find(DOC_ATTRIBUTE) {
+        let start = position + found;
+        let rest = &source[start..];
+
+        let mut lines = vec![];
+        let mut end = 0;
+        while let Some((line, length)) = parse_doc_attribute(&rest[end..]) {
+            lines.push(line);
+            end += length;
+
+            let whitespace = rest[end..].len() - rest[end..].trim_start().len();
+            if !rest[end + whitespace..].starts_with(DOC_ATTRIBUTE) {
+                break;
+            }
+            end += whitespace;
+        }
+
+        // Not an attribute this function understands, like one with a `"` in the middle
+        if lines.is_empty() {
+            position = start + DOC_ATTRIBUTE.len();
+            continue;
+        }
+
+        // Doc comments like `/// Example` start with the space after the slashes
+        let doxygen = lines
+            .iter()
+            .flat_map(|line| line.lines())
+            .map(|line| line.strip_prefix(' ').unwrap_or(line))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        if doxygen.contains("deprecated") {
+            if let Ok(comment) = convert_with_options(doxygen.clone(), options) {
+                report
+                    .deprecations
+                    .extend(comment.deprecated.map(|message| Deprecation {
+                        item_hint: item_hint(&rest[end..]).into(),
+                        message,
+                        since: comment.since,
+                    }));
+            }
+        }
+
+        debug!(item = item_hint(&rest[end..]), "translating a comment");
+        let translation = if options.cache_comments {
+            cache
+                .entry(doxygen)
+                .or_insert_with_key(|doxygen| Translation::new(doxygen.clone(), options))
+                .clone()
+        } else {
+            Translation::new(doxygen, options)
+        };
+
+        report.stats.merge(&translation.stats);
+        report.warnings.extend(translation.warnings);
+
+        if let Some(replacement) = translation.attribute {
+            if replacement != rest[..end] {
+                edits.push(Edit {
+                    range: start..start + end,
+                    replacement,
+                });
+            }
+        }
+
+        position = start + end;
+    }
+
+    (edits, report)
+}
+
+/// Returns the first line of the item at the start of the source, skipping its attributes.
+fn item_hint(source: &str) -> &str {
+    source
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with("#["))
+        .unwrap_or_default()
+}
+
+/// Finds the files under `root` matching the glob `pattern`.
+fn find_files(root: impl AsRef<Path>, pattern: &str) -> io::Result<Vec<PathBuf>> {
+    let pattern = root.as_ref().join(pattern);
+    let paths = glob::glob(&pattern.to_string_lossy())
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+
+    let mut files = vec![];
+    for path in paths {
+        let path = path.map_err(io::Error::from)?;
+        if path.is_file() {
+            files.push(path);
+        }
+    }
+
+    Ok(files)
+}
+
+/// Parses the doc attribute at the start of the input, returning its (unescaped) value and its
+/// length.
+fn parse_doc_attribute(input: &str) -> Option<(String, usize)> {
+    let literal = input.strip_prefix(DOC_ATTRIBUTE)?;
+    let mut value = String::new();
+    let mut chars = literal.char_indices();
+
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '"' => {
+                let rest = literal[index + 1..].strip_prefix(']')?;
+                return Some((value, input.len() - rest.len()));
+            }
+            '\\' => match chars.next()?.1 {
+                'n' => value.push('\n'),
+                'r' => value.push('\r'),
+                't' => value.push('\t'),
+                '0' => value.push('\0'),
+                c @ ('\\' | '"' | '\'') => value.push(c),
+                'u' => {
+                    let (start, _) = chars.next().filter(|(_, c)| *c == '{')?;
+                    let (end, _) = chars.find(|(_, c)| *c == '}')?;
+                    let code = u32::from_str_radix(&literal[start + 1..end], 16).ok()?;
+                    value.push(char::from_u32(code)?);
+                }
+                'x' => {
+                    let (start, _) = chars.next()?;
+                    let (end, _) = chars.next()?;
+                    let code = u8::from_str_radix(&literal[start..=end], 16).ok()?;
+                    value.push(char::from(code));
+                }
+                // A line continuation, which skips the whitespace at the start of the next line
+                '\n' => {
+                    let skipped =
+                        literal[index + 2..].len() - literal[index + 2..].trim_start().len();
+                    for _ in literal[index + 2..index + 2 + skipped].chars() {
+                        chars.next();
+                    }
+                }
+                _ => return None,
+            },
+            _ => value.push(c),
+        }
+    }
+
+    None
+}
+
+/// Creates the doc attributes of the Rustdoc for generated Rust source, one per line like the ones
+/// of doc comments. The lines start with a space, like the lines of `/// Example`, and the quotes
+/// and backslashes are escaped.
+///
+/// # Examples
+///
+/// ```
+/// use doxygen_rs::transform::to_doc_attrs;
+///
+/// assert_eq!(
+///     to_doc_attrs("A \"quoted\" C:\\path\n\n# Arguments"),
+///     [
+///         r#"#[doc = " A \"quoted\" C:\\path"]"#,
+///         r#"#[doc = ""]"#,
+///         r#"#[doc = " # Arguments"]"#,
+///     ]
+/// );
+/// ```
+pub fn to_doc_attrs(converted: &str) -> Vec<String> {
+    to_doc_literals(converted)
+        .into_iter()
+        .map(|literal| format!("#[doc = {literal}]"))
+        .collect()
+}
+
+/// Creates the string literals of the attributes of [`to_doc_attrs`], like `" Example"` (with the
+/// quotes), for code generators writing the attributes themselves.
+pub fn to_doc_literals(converted: &str) -> Vec<String> {
+    converted
+        .split('\n')
+        .map(|line| format!("{:?}", doc_line(line)))
+        .collect()
+}
+
+/// Creates the tokens of the attributes of [`to_doc_attrs`], for code generators built on `quote`
+/// or `syn`. Requires the `proc-macro2` feature.
+///
+/// # Examples
+///
+/// ```
+/// use doxygen_rs::transform::to_token_stream;
+///
+/// let tokens = to_token_stream("Example\n\n# Arguments");
+///
+/// assert_eq!(
+///     tokens.to_string(),
+///     r#"# [doc = " Example"] # [doc = ""] # [doc = " # Arguments"]"#
+/// );
+/// ```
+#[cfg(feature = "proc-macro2")]
+pub fn to_token_stream(converted: &str) -> proc_macro2::TokenStream {
+    use proc_macro2::{Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenTree};
+
+    converted
+        .split('\n')
+        .flat_map(|line| {
+            let attribute = [
+                TokenTree::Ident(Ident::new("doc", Span::call_site())),
+                TokenTree::Punct(Punct::new('=', Spacing::Alone)),
+                TokenTree::Literal(Literal::string(&doc_line(line))),
+            ];
+
+            [
+                TokenTree::Punct(Punct::new('#', Spacing::Alone)),
+                TokenTree::Group(Group::new(
+                    Delimiter::Bracket,
+                    attribute.into_iter().collect(),
+                )),
+            ]
+        })
+        .collect()
+}
+
+/// Creates the doc attribute of the Rustdoc, starting its lines with a space like doc comments.
+fn doc_attribute(rustdoc: &str) -> String {
+    let value = rustdoc
+        .split('\n')
+        .map(doc_line)
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    format!("#[doc = {value:?}]")
+}
+
+/// Starts the line with a space, like the lines of doc comments. Empty lines are kept empty.
+fn doc_line(line: &str) -> String {
+    if line.is_empty() {
+        String::new()
+    } else {
+        format!(" {line}")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn converts_ranges() {
+        let source = "//! @brief Crate.\n\n/// Unrelated.\nfn a() {}\n\n/// @brief Frees memory.\n/// @param ptr The memory.\nfn free() {}\n#[doc = \" @b Bold\"]\nfn bold() {}";
+
+        let edit = convert_range(source, 60..65).unwrap();
+        assert_eq!(
+            &source[edit.range],
+            "/// @brief Frees memory.\n/// @param ptr The memory."
+        );
+        assert_eq!(
+            edit.replacement,
+            "/// Frees memory.\n///\n/// # Arguments\n///\n/// * `ptr` - The memory."
+        );
+
+        let edit = convert_range(source, 0..2).unwrap();
+        assert_eq!(
+            (edit.range, edit.replacement.as_str()),
+            (0..17, "//! Crate.")
+        );
+
+        let edit = convert_range(source, 110..112).unwrap();
+        assert_eq!(edit.replacement, "#[doc = \" **Bold**\"]");
+
+        // Unchanged, outside of the comments, and spanning more than a comment
+        assert_eq!(convert_range(source, 22..22), None);
+        assert_eq!(convert_range(source, 38..38), None);
+        assert_eq!(convert_range(source, 0..60), None);
+    }
+
+    #[test]
+    fn transforms_attributes() {
+        let source = "extern \"C\" {\n    #[doc = \" @brief Frees \\\"memory\\\"\"]\n    #[doc = \" @param[in] ptr The memory.\"]\n    pub fn free(ptr: *mut u8);\n}";
+        let (transformed, report) = transform_source(source, &Options::default());
+
+        assert_eq!(
+            transformed,
+            "extern \"C\" {\n    #[doc = \" Frees \\\"memory\\\"\\n\\n # Arguments\\n\\n * `ptr` (direction in) - The memory.\"]\n    pub fn free(ptr: *mut u8);\n}"
+        );
+        assert_eq!(report.stats.comments, 1);
+        assert_eq!(report.stats.tags["param"], 1);
+    }
+
+    #[test]
+    fn parses_escapes() {
+        assert_eq!(
+            parse_doc_attribute("#[doc = \"a\\n\\t\\u{1F600}\\x41\\\\\"] fn"),
+            Some(("a\n\t😀A\\".into(), 31))
+        );
+        assert_eq!(parse_doc_attribute("#[doc = \"a\" ]"), None);
+    }
+
+    #[test]
+    fn reports_warnings() {
+        let (transformed, report) =
+            transform_source("#[doc = \"Missing @c\"]\nstruct A;", &Options::default());
+
+        assert_eq!(transformed, "#[doc = \" Missing @c\"]\nstruct A;");
+        assert_eq!(report.warnings.len(), 1);
+    }
+
+    #[test]
+    fn transforms_directory() {
+        let root = std::env::temp_dir().join(format!("doxygen-rs-{}", std::process::id()));
+        fs::create_dir_all(root.join("bindings/nested")).unwrap();
+        fs::write(root.join("bindings/a.rs"), "#[doc = \"@b A\"]\nstruct A;").unwrap();
+        fs::write(
+            root.join("bindings/nested/b.rs"),
+            "#[doc = \"@c\"]\nstruct B;",
+        )
+        .unwrap();
+        fs::write(root.join("bindings/c.txt"), "#[doc = \"@b C\"]").unwrap();
+
+        let reports = transform_directory(&root, "bindings/**/*.rs", &Options::default()).unwrap();
+        let a = fs::read_to_string(root.join("bindings/a.rs")).unwrap();
+        let c = fs::read_to_string(root.join("bindings/c.txt")).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(reports.len(), 2);
+        assert!(reports[0].0.ends_with("bindings/a.rs"));
+        assert!(reports[0].1.warnings.is_empty());
+        assert!(reports[1].0.ends_with("bindings/nested/b.rs"));
+        assert_eq!(reports[1].1.warnings.len(), 1);
+        assert_eq!(a, "#[doc = \" **A**\"]\nstruct A;");
+        assert_eq!(c, "#[doc = \"@b C\"]");
+    }
+
+    #[test]
+    fn diffs_directory() {
+        let root = std::env::temp_dir().join(format!("doxygen-rs-diff-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("a.rs"), "#[doc = \"@b A\"]\nstruct A;\n").unwrap();
+        fs::write(root.join("b.rs"), "#[doc = \" B\"]\nstruct B;\n").unwrap();
+
+        let (diff, reports) = diff_directory(&root, "*.rs", &Options::default()).unwrap();
+        let a = fs::read_to_string(root.join("a.rs")).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        let path = root.join("a.rs").to_string_lossy().into_owned();
+        assert_eq!(
+            diff,
+            format!("--- a/{path}\n+++ b/{path}\n@@ -1,2 +1,2 @@\n-#[doc = \"@b A\"]\n+#[doc = \" **A**\"]\n struct A;\n")
+        );
+        assert_eq!(reports.len(), 2);
+        assert_eq!(a, "#[doc = \"@b A\"]\nstruct A;\n");
+    }
+
+    #[test]
+    fn transforms_bindgen_output() {
+        let source = include_str!("../tests/assets/example-bindgen.rs");
+        let (transformed, report) = transform_source(source, &Options::default());
+
+        assert!(!transformed.contains("@brief"));
+        assert!(transformed.contains("pub fn svcControlMemory("));
+        assert_eq!(report.stats.comments, 1);
+    }
+
+    #[test]
+    fn reports_deprecations() {
+        let source = "#[doc = \" @deprecated Use @c open2.\"]\n#[doc = \" @since 1.2\"]\n#[must_use]\npub fn open();\n#[doc = \" @brief Close.\"]\npub fn close();";
+        let (_, report) = transform_source(source, &Options::default());
+
+        assert_eq!(
+            report.deprecations,
+            vec![Deprecation {
+                item_hint: "pub fn open();".into(),
+                message: "Use `open2`.".into(),
+                since: Some("1.2".into()),
+            }]
+        );
+    }
+
+    #[test]
+    fn keeps_comments_exceeding_limits() {
+        let source =
+            "#[doc = \" @brief A long comment\"]\npub fn a();\n#[doc = \" @b B\"]\npub fn b();";
+        let options = Options {
+            limits: crate::options::Limits {
+                max_input_len: Some(8),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let (transformed, report) = transform_source(source, &options);
+
+        assert!(transformed.starts_with("#[doc = \" @brief A long comment\"]"));
+        assert!(transformed.contains("#[doc = \" **B**\"]"));
+        assert_eq!(report.warnings.len(), 1);
+
+        let source = "/// @brief A long comment\nfn a() {}";
+        assert_eq!(convert_range_with_options(source, 4..9, &options), None);
+    }
+
+    #[test]
+    fn caches_comments() {
+        let source = "#[doc = \" @b A @nope\"]\nfn a();\n#[doc = \" @b A @nope\"]\nfn b();";
+        let options = Options {
+            cache_comments: true,
+            ..Default::default()
+        };
+        let (transformed, report) = transform_source(source, &options);
+
+        assert_eq!(transformed, transform_source(source, &Options::default()).0);
+        assert_eq!(transformed.matches("**A**").count(), 2);
+        assert_eq!(report.stats.comments, 2);
+        assert_eq!(report.stats.unknown_tags["nope"], 2);
+    }
+
+    #[test]
+    fn doc_literals() {
+        assert_eq!(
+            to_doc_literals("Tab\there\n\nC:\\"),
+            [r#"" Tab\there""#, r#""""#, r#"" C:\\""#]
+        );
+    }
+}